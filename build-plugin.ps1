@@ -4,4 +4,5 @@ if (Test-Path $pluginPath)
 {
     Copy-Item $pluginPath $backupPath
 }
-&cargo build --package handmade_hero_plugin
\ No newline at end of file
+&cargo build --package handmade_hero_plugin
+Copy-Item "handmade_hero_plugin/levels/world.level" "target/debug/world.level"
\ No newline at end of file
@@ -0,0 +1,15 @@
+use std::process::Command;
+
+fn main() {
+    // Plugins record the compiler that built them so hosts can refuse a plugin whose
+    // Rust ABI might not match their own.
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map_or_else(|| String::from("unknown"), |v| v.trim().to_string());
+    println!("cargo:rustc-env=HANDMADE_HERO_RUSTC_VERSION={version}");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
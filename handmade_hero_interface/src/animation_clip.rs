@@ -0,0 +1,123 @@
+use crate::application_error::{ApplicationError, Result};
+use crate::units::si::time::Time;
+use uom::num::Zero;
+
+/// A sprite sheet frame and how long it is shown.
+#[derive(Debug, Copy, Clone)]
+pub struct AnimationFrame {
+    pub sprite: usize,
+    pub duration: Time,
+}
+
+/// A looping sequence of sprite sheet frames.
+///
+/// A clip only describes the animation. Whatever is animated keeps track of how long
+/// it has been playing, typically by adding up `GameState::frame_duration`, so the
+/// animation stays in step with the simulation when it is recorded and replayed.
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    frames: Vec<AnimationFrame>,
+    duration: Time,
+}
+
+impl AnimationClip {
+    /// # Errors
+    /// An error is returned if there are no frames or a frame is not shown for a
+    /// positive duration.
+    pub fn new(frames: Vec<AnimationFrame>) -> Result<Self> {
+        if frames.is_empty() {
+            return Err(ApplicationError::new(
+                "An animation needs at least one frame",
+            ));
+        }
+        if frames.iter().any(|f| f.duration <= Time::zero()) {
+            return Err(ApplicationError::new(
+                "Animation frames must be shown for a positive duration",
+            ));
+        }
+        let duration = frames.iter().map(|f| f.duration).sum();
+        Ok(Self { frames, duration })
+    }
+
+    /// A clip showing each of the sprites for the same duration.
+    ///
+    /// # Errors
+    /// An error is returned if there are no sprites or the duration is not positive.
+    pub fn uniform(sprites: impl IntoIterator<Item = usize>, duration: Time) -> Result<Self> {
+        let frames = sprites
+            .into_iter()
+            .map(|sprite| AnimationFrame { sprite, duration })
+            .collect();
+        Self::new(frames)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// How long it takes to play every frame once.
+    #[inline]
+    #[must_use]
+    pub fn duration(&self) -> Time {
+        self.duration
+    }
+
+    /// The sprite to show once the clip has been playing for the given time, looping
+    /// back to the start after the last frame.
+    #[must_use]
+    pub fn sprite_at(&self, elapsed: Time) -> usize {
+        let mut remaining = elapsed % self.duration;
+        if remaining < Time::zero() {
+            remaining += self.duration;
+        }
+        for frame in &self.frames {
+            if remaining < frame.duration {
+                return frame.sprite;
+            }
+            remaining -= frame.duration;
+        }
+        // Rounding can leave a sliver of time past the last frame.
+        self.frames[self.frames.len() - 1].sprite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation_clip::{AnimationClip, AnimationFrame};
+    use crate::units::si::time::Time;
+    use uom::si::time::second;
+
+    fn seconds(value: f32) -> Time {
+        Time::new::<second>(value)
+    }
+
+    #[test]
+    fn test_sprite_at_loops() {
+        let clip = AnimationClip::new(vec![
+            AnimationFrame {
+                sprite: 4,
+                duration: seconds(0.5f32),
+            },
+            AnimationFrame {
+                sprite: 7,
+                duration: seconds(0.25f32),
+            },
+        ])
+        .unwrap();
+        assert_eq!(seconds(0.75f32), clip.duration());
+        let sprites: Vec<_> = [0f32, 0.4f32, 0.5f32, 0.7f32, 0.75f32, 1.3f32]
+            .into_iter()
+            .map(|s| clip.sprite_at(seconds(s)))
+            .collect();
+        assert_eq!(vec![4, 4, 7, 7, 4, 7], sprites);
+    }
+
+    #[test]
+    fn test_rejects_invalid_clips() {
+        assert!(AnimationClip::new(Vec::new()).is_err());
+        assert!(AnimationClip::uniform([1, 2], seconds(0f32)).is_err());
+        assert!(AnimationClip::uniform([1, 2], seconds(0.1f32)).is_ok());
+    }
+}
@@ -1,10 +1,14 @@
+use crate::application_error::Result;
 use crate::audio_context::AudioContext;
 use crate::initialize_context::InitializeContext;
 use crate::input_context::InputContext;
 use crate::render_context::RenderContext;
 
 pub trait Application {
-    fn initialize(&self, context: InitializeContext<'_>);
+    /// # Errors
+    /// An error is returned if the game cannot be set up, such as when its world fails
+    /// to load. Hosts report it rather than running the game.
+    fn initialize(&self, context: InitializeContext<'_>) -> Result<()>;
 
     fn process_input(&self, context: InputContext<'_>);
 
@@ -1,9 +1,29 @@
 use std::error::Error;
 
+/// Classifies an `ApplicationError` so callers can react to specific failures
+/// without inspecting the message.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    #[default]
+    Other,
+    /// The plugin was built against a different version of the interface than the host.
+    IncompatiblePlugin,
+    /// A recording was made by an incompatible version of the game or is not a recording.
+    IncompatibleRecording,
+    /// A recording is truncated or its contents fail their checksum.
+    CorruptRecording,
+    /// An image file is truncated, is not a BMP file or uses an unsupported format.
+    MalformedBitmap,
+    /// A level file is malformed, such as a tile map with missing rows or bad tile ids.
+    MalformedLevel,
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("{message}")]
 pub struct ApplicationError {
     message: String,
+    kind: ErrorKind,
     #[source]
     source: Option<Box<dyn Error>>,
 }
@@ -14,6 +34,7 @@ impl ApplicationError {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            kind: ErrorKind::Other,
             source: None,
         }
     }
@@ -23,9 +44,23 @@ impl ApplicationError {
     pub fn wrap(message: impl Into<String>, source: impl Error + 'static) -> Self {
         Self {
             message: message.into(),
+            kind: ErrorKind::Other,
             source: Some(Box::new(source)),
         }
     }
+
+    #[inline]
+    #[must_use]
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ApplicationError>;
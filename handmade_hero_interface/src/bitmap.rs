@@ -0,0 +1,398 @@
+//! Images loaded from BMP files.
+//!
+//! Uncompressed 24-bit and 32-bit files are supported, stored either bottom-up or
+//! top-down, including 32-bit files whose channels are described by bit masks, such as
+//! those with an alpha channel.
+
+use crate::application_error::{ApplicationError, ErrorKind, Result};
+use crate::color::Color;
+use crate::rectangle::Rectangle;
+use crate::render_target::{RenderTarget, RowOrder};
+use std::path::Path;
+
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const COMPRESSION_RGB: u32 = 0;
+const COMPRESSION_BITFIELDS: u32 = 3;
+const COMPRESSION_ALPHA_BITFIELDS: u32 = 6;
+
+/// An image whose rows are stored bottom row first, with straight alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color<u8>>,
+}
+
+impl Bitmap {
+    /// # Errors
+    /// An error is returned if the number of pixels is not the width times the height.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color<u8>>) -> Result<Self> {
+        if width.checked_mul(height) != Some(pixels.len()) {
+            return Err(ApplicationError::new(format!(
+                "A {width}x{height} bitmap cannot hold {} pixels",
+                pixels.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// # Errors
+    /// An error is returned if the file cannot be read. An `ErrorKind::MalformedBitmap`
+    /// error is returned if it is not a BMP file this decoder supports.
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| {
+            ApplicationError::wrap(format!("Could not read the bitmap {}", path.display()), e)
+        })?;
+        Self::from_bmp(&bytes)
+    }
+
+    /// Decodes the contents of a BMP file.
+    ///
+    /// # Errors
+    /// An `ErrorKind::MalformedBitmap` error is returned if the file is truncated, is
+    /// not a BMP file or uses a format this decoder does not support.
+    pub fn from_bmp(bytes: &[u8]) -> Result<Self> {
+        if bytes.get(0..2) != Some(b"BM") {
+            return Err(malformed("The file is not a BMP file"));
+        }
+        let data_offset = read_u32(bytes, 10)? as usize;
+        let header_size = read_u32(bytes, FILE_HEADER_SIZE)? as usize;
+        if header_size < INFO_HEADER_SIZE {
+            return Err(malformed(format!(
+                "BMP headers of {header_size} bytes are not supported"
+            )));
+        }
+        let width = read_i32(bytes, 18)?;
+        let height = read_i32(bytes, 22)?;
+        let bits_per_pixel = read_u16(bytes, 28)?;
+        let compression = read_u32(bytes, 30)?;
+
+        // A negative height means the first row in the file is the top of the image.
+        let row_order = if height < 0 {
+            RowOrder::TopDown
+        } else {
+            RowOrder::BottomUp
+        };
+        let Ok(width) = usize::try_from(width) else {
+            return Err(malformed("The bitmap has a negative width"));
+        };
+        let height = height.unsigned_abs() as usize;
+        if width == 0 || height == 0 {
+            return Err(malformed("The bitmap is empty"));
+        }
+
+        let masks = match (bits_per_pixel, compression) {
+            (24, COMPRESSION_RGB) => None,
+            // The fourth byte is unused, rather than alpha, in plain 32-bit files.
+            (32, COMPRESSION_RGB) => Some(ChannelMasks::opaque()),
+            (32, COMPRESSION_BITFIELDS | COMPRESSION_ALPHA_BITFIELDS) => {
+                // The masks follow the 40-byte header, or are part of a larger one.
+                let mask_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+                let has_alpha = compression == COMPRESSION_ALPHA_BITFIELDS || header_size >= 56;
+                let alpha = if has_alpha {
+                    read_u32(bytes, mask_offset + 12)?
+                } else {
+                    0
+                };
+                Some(ChannelMasks {
+                    red: Mask::new(read_u32(bytes, mask_offset)?)?,
+                    green: Mask::new(read_u32(bytes, mask_offset + 4)?)?,
+                    blue: Mask::new(read_u32(bytes, mask_offset + 8)?)?,
+                    alpha: Mask::new(alpha)?,
+                })
+            }
+            _ => {
+                return Err(malformed(format!(
+                    "{bits_per_pixel}-bit BMP files with compression {compression} are not supported"
+                )));
+            }
+        };
+
+        let bytes_per_pixel = usize::from(bits_per_pixel / 8);
+        // Rows are padded to a multiple of 4 bytes.
+        let stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+        let data = stride
+            .checked_mul(height)
+            .and_then(|length| bytes.get(data_offset..data_offset.checked_add(length)?))
+            .ok_or_else(|| malformed("The bitmap's pixel data is truncated"))?;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let file_row = match row_order {
+                RowOrder::TopDown => height - 1 - y,
+                RowOrder::BottomUp => y,
+            };
+            let row = &data[file_row * stride..][..width * bytes_per_pixel];
+            pixels.extend(row.chunks_exact(bytes_per_pixel).map(|p| match &masks {
+                None => Color::from_rgb(p[2], p[1], p[0]),
+                Some(masks) => masks.decode(u32::from_le_bytes([p[0], p[1], p[2], p[3]])),
+            }));
+        }
+        Self::new(width, height, pixels)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bounds(&self) -> Rectangle<usize> {
+        Rectangle::new(0, 0, self.height, self.width)
+    }
+
+    /// The pixels, bottom row first.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> &[Color<u8>] {
+        &self.pixels
+    }
+
+    #[must_use]
+    pub fn row(&self, y: usize) -> Option<&[Color<u8>]> {
+        if y >= self.height {
+            return None;
+        }
+        Some(&self.pixels[y * self.width..][..self.width])
+    }
+
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color<u8>> {
+        self.row(y).and_then(|r| r.get(x)).copied()
+    }
+
+    /// A render target for drawing into the bitmap itself.
+    ///
+    /// # Panics
+    /// Panics if the bitmap's dimensions are inconsistent, which `new` prevents.
+    #[must_use]
+    pub fn target(&mut self) -> RenderTarget<'_> {
+        RenderTarget::new(
+            &mut self.pixels,
+            self.width,
+            self.height,
+            self.width,
+            RowOrder::BottomUp,
+        )
+        .expect("A bitmap always holds width * height pixels")
+    }
+}
+
+#[derive(Debug)]
+struct ChannelMasks {
+    red: Mask,
+    green: Mask,
+    blue: Mask,
+    alpha: Mask,
+}
+
+impl ChannelMasks {
+    fn opaque() -> Self {
+        Self {
+            red: Mask::byte(2),
+            green: Mask::byte(1),
+            blue: Mask::byte(0),
+            alpha: Mask::default(),
+        }
+    }
+
+    fn decode(&self, value: u32) -> Color<u8> {
+        let alpha = if self.alpha.bits == 0 {
+            u8::MAX
+        } else {
+            self.alpha.extract(value)
+        };
+        Color::from_rgba(
+            self.red.extract(value),
+            self.green.extract(value),
+            self.blue.extract(value),
+            alpha,
+        )
+    }
+}
+
+/// A contiguous run of bits holding one channel of a pixel.
+#[derive(Debug, Default)]
+struct Mask {
+    bits: u32,
+    shift: u32,
+    maximum: u32,
+}
+
+impl Mask {
+    fn new(mask: u32) -> Result<Self> {
+        if mask == 0 {
+            return Ok(Self::default());
+        }
+        let shift = mask.trailing_zeros();
+        let maximum = mask >> shift;
+        // A mask of all ones is contiguous too, even though one more than it overflows.
+        if maximum & maximum.wrapping_add(1) != 0 {
+            return Err(malformed(format!(
+                "The channel mask {mask:#010X} is not contiguous"
+            )));
+        }
+        Ok(Self {
+            bits: mask,
+            shift,
+            maximum,
+        })
+    }
+
+    fn byte(index: u32) -> Self {
+        Self {
+            bits: 0xFF << (index * 8),
+            shift: index * 8,
+            maximum: 0xFF,
+        }
+    }
+
+    /// The channel's value, scaled to 8 bits.
+    fn extract(&self, value: u32) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let channel = u64::from((value & self.bits) >> self.shift);
+        let scaled = (channel * 255 + u64::from(self.maximum) / 2) / u64::from(self.maximum);
+        u8::try_from(scaled).unwrap_or(u8::MAX)
+    }
+}
+
+fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| malformed("The BMP header is truncated"))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    read_array(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    read_array(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn read_i32(bytes: &[u8], offset: usize) -> Result<i32> {
+    read_array(bytes, offset).map(i32::from_le_bytes)
+}
+
+fn malformed(message: impl Into<String>) -> ApplicationError {
+    ApplicationError::new(message).with_kind(ErrorKind::MalformedBitmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application_error::ErrorKind;
+    use crate::bitmap::Bitmap;
+    use crate::color::Color;
+
+    /// Builds a BMP file from rows given top row first. 32-bit files get a 56-byte
+    /// header with masks for BGRA pixels.
+    fn bmp(rows: &[&[[u8; 4]]], bits_per_pixel: u16, top_down: bool) -> Vec<u8> {
+        let header_size: u32 = if bits_per_pixel == 32 { 56 } else { 40 };
+        let width = rows[0].len();
+        let bytes_per_pixel = usize::from(bits_per_pixel / 8);
+        let stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+        let data_offset = 14 + header_size;
+        let height = i32::try_from(rows.len()).unwrap();
+
+        let mut bytes = b"BM".to_vec();
+        let file_size = data_offset as usize + stride * rows.len();
+        bytes.extend(u32::try_from(file_size).unwrap().to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend(data_offset.to_le_bytes());
+        bytes.extend(header_size.to_le_bytes());
+        bytes.extend(i32::try_from(width).unwrap().to_le_bytes());
+        bytes.extend((if top_down { -height } else { height }).to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(bits_per_pixel.to_le_bytes());
+        let compression: u32 = if bits_per_pixel == 32 { 3 } else { 0 };
+        bytes.extend(compression.to_le_bytes());
+        bytes.extend([0; 20]);
+        if bits_per_pixel == 32 {
+            for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+                bytes.extend(mask.to_le_bytes());
+            }
+        }
+
+        let mut ordered: Vec<_> = rows.to_vec();
+        if !top_down {
+            ordered.reverse();
+        }
+        for row in ordered {
+            let start = bytes.len();
+            for &[red, green, blue, alpha] in row {
+                bytes.extend([blue, green, red, alpha].iter().take(bytes_per_pixel));
+            }
+            bytes.resize(start + stride, 0);
+        }
+        bytes
+    }
+
+    const RED: [u8; 4] = [0xFF, 0, 0, 0xFF];
+    const GREEN: [u8; 4] = [0, 0xFF, 0, 0xFF];
+    const BLUE: [u8; 4] = [0, 0, 0xFF, 0x80];
+
+    #[test]
+    fn test_24_bit_bottom_up() {
+        let bytes = bmp(&[&[RED, GREEN, BLUE], &[BLUE, RED, GREEN]], 24, false);
+        let bitmap = Bitmap::from_bmp(&bytes).unwrap();
+        assert_eq!((3, 2), (bitmap.width(), bitmap.height()));
+        // Rows are padded, and 24-bit pixels are always opaque.
+        assert_eq!(Some(Color::from_rgb(0, 0, 0xFF)), bitmap.pixel(0, 0));
+        assert_eq!(Some(Color::from_rgb(0xFF, 0, 0)), bitmap.pixel(0, 1));
+        assert_eq!(Some(Color::from_rgb(0, 0, 0xFF)), bitmap.pixel(2, 1));
+    }
+
+    #[test]
+    fn test_32_bit_top_down_with_alpha() {
+        let top_down = bmp(&[&[RED, GREEN], &[BLUE, RED]], 32, true);
+        let bottom_up = bmp(&[&[RED, GREEN], &[BLUE, RED]], 32, false);
+        let bitmap = Bitmap::from_bmp(&top_down).unwrap();
+        assert_eq!(Bitmap::from_bmp(&bottom_up).unwrap(), bitmap);
+        assert_eq!(Some(Color::from_rgba(0, 0, 0xFF, 0x80)), bitmap.pixel(0, 0));
+        assert_eq!(Some(Color::from_rgb(0, 0xFF, 0)), bitmap.pixel(1, 1));
+    }
+
+    #[test]
+    fn test_rejects_malformed_files() {
+        let bytes = bmp(&[&[RED, GREEN], &[BLUE, RED]], 32, false);
+        let truncated = &bytes[..bytes.len() - 1];
+        let mut wrong_depth = bytes.clone();
+        wrong_depth[28] = 16;
+        let mut scattered_mask = bytes.clone();
+        scattered_mask[54] = 0b0101;
+        for bytes in [
+            &b"GIF89a"[..],
+            &bytes[..20],
+            truncated,
+            &wrong_depth,
+            &scattered_mask,
+        ] {
+            let error = Bitmap::from_bmp(bytes).unwrap_err();
+            assert_eq!(ErrorKind::MalformedBitmap, error.kind(), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_accepts_all_ones_mask() {
+        let mut bytes = bmp(&[&[RED, GREEN], &[BLUE, RED]], 32, false);
+        bytes[54..58].copy_from_slice(&u32::MAX.to_le_bytes());
+        let bitmap = Bitmap::from_bmp(&bytes).unwrap();
+        assert_eq!(0xFF, bitmap.pixel(0, 1).unwrap().red());
+    }
+}
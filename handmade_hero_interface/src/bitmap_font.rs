@@ -0,0 +1,280 @@
+//! Fixed-width fonts for drawing text with a `Rasterizer`.
+//!
+//! Fonts are described as text, so they can be edited by hand. Each glyph is a line
+//! holding the character in square brackets, followed by one line per row of pixels,
+//! top row first, with `#` for a set pixel and `.` for a clear one. The embedded font
+//! covers printable ASCII with 5x8 glyphs, whose last row is for descenders.
+
+use crate::application_error::{ApplicationError, Result};
+use crate::color::Color;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const EMBEDDED_FONT: &str = include_str!("font_5x8.txt");
+
+/// The character drawn in place of any the font does not have.
+const REPLACEMENT: char = '?';
+
+#[derive(Debug, Clone)]
+pub struct BitmapFont {
+    glyph_width: usize,
+    glyph_height: usize,
+    /// Whether each pixel of a glyph is set, top row first.
+    glyphs: HashMap<char, Vec<bool>>,
+}
+
+impl BitmapFont {
+    /// The 5x8 font built into the crate.
+    ///
+    /// # Panics
+    /// Panics if the embedded font is malformed, which its tests guard against.
+    #[must_use]
+    pub fn embedded() -> &'static Self {
+        static FONT: OnceLock<BitmapFont> = OnceLock::new();
+        FONT.get_or_init(|| Self::parse(EMBEDDED_FONT).expect("The embedded font is malformed"))
+    }
+
+    /// # Errors
+    /// An error is returned if a glyph's rows are not all the same size as the first
+    /// glyph's, or a line is neither a glyph header nor a row of pixels.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut glyphs: HashMap<char, Vec<Vec<bool>>> = HashMap::new();
+        let mut current = None;
+        for (index, line) in source.lines().enumerate() {
+            let mut characters = line.chars();
+            if let (Some('['), Some(character), Some(']'), None) = (
+                characters.next(),
+                characters.next(),
+                characters.next(),
+                characters.next(),
+            ) {
+                glyphs.insert(character, Vec::new());
+                current = Some(character);
+                continue;
+            }
+            let row = line
+                .chars()
+                .map(|c| match c {
+                    '#' => Some(true),
+                    '.' => Some(false),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            match (current.and_then(|c| glyphs.get_mut(&c)), row) {
+                (Some(rows), Some(row)) if !row.is_empty() => rows.push(row),
+                _ => {
+                    return Err(ApplicationError::new(format!(
+                        "Line {} of the font is not a glyph header or a row of pixels",
+                        index + 1
+                    )));
+                }
+            }
+        }
+
+        let Some(first) = glyphs.values().next() else {
+            return Err(ApplicationError::new("The font has no glyphs"));
+        };
+        let glyph_height = first.len();
+        let glyph_width = first.first().map_or(0, Vec::len);
+        let mut flattened = HashMap::with_capacity(glyphs.len());
+        for (character, rows) in glyphs {
+            if rows.is_empty() {
+                return Err(ApplicationError::new(format!(
+                    "The glyph for {character:?} has no rows of pixels"
+                )));
+            }
+            if rows.len() != glyph_height || rows.iter().any(|r| r.len() != glyph_width) {
+                return Err(ApplicationError::new(format!(
+                    "The glyph for {character:?} is not {glyph_width}x{glyph_height}"
+                )));
+            }
+            flattened.insert(character, rows.concat());
+        }
+        Ok(Self {
+            glyph_width,
+            glyph_height,
+            glyphs: flattened,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn glyph_width(&self) -> usize {
+        self.glyph_width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn glyph_height(&self) -> usize {
+        self.glyph_height
+    }
+
+    /// The distance from the start of one character to the next, before scaling.
+    #[inline]
+    #[must_use]
+    pub fn advance(&self) -> usize {
+        self.glyph_width + 1
+    }
+
+    /// The distance from the top of one line to the next, before scaling.
+    #[inline]
+    #[must_use]
+    pub fn line_height(&self) -> usize {
+        self.glyph_height + 1
+    }
+
+    /// Whether each pixel of the character's glyph is set, top row first. Characters
+    /// the font does not have use the glyph for '?', if there is one.
+    #[must_use]
+    pub fn glyph(&self, character: char) -> Option<&[bool]> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&REPLACEMENT))
+            .map(Vec::as_slice)
+    }
+
+    /// Splits the text into the lines it is drawn as. Lines break at newlines and,
+    /// if the style has a wrap width, between words that would not fit. Words longer
+    /// than a whole line are broken wherever they reach the edge.
+    #[must_use]
+    pub fn lines<'t>(&self, text: &'t str, style: &TextStyle) -> Vec<&'t str> {
+        let Some(wrap_width) = style.wrap_width else {
+            return text.lines().collect();
+        };
+        // The last character on a line needs no spacing after it.
+        let columns = (wrap_width.saturating_add(style.scale)
+            / self.advance().saturating_mul(style.scale))
+        .max(1);
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut rest = paragraph;
+            loop {
+                let Some((end, character)) = rest.char_indices().nth(columns) else {
+                    lines.push(rest);
+                    break;
+                };
+                // Break at the last space that fits, or mid-word if there is none.
+                let (line, next) = match rest[..end + character.len_utf8()].rfind(' ') {
+                    Some(space) if space > 0 => (&rest[..space], &rest[space + 1..]),
+                    _ => (&rest[..end], &rest[end..]),
+                };
+                lines.push(line.trim_end());
+                rest = next.trim_start();
+            }
+        }
+        lines
+    }
+
+    /// The width and height, in pixels, of the text when drawn in the style.
+    #[must_use]
+    pub fn measure(&self, text: &str, style: &TextStyle) -> (usize, usize) {
+        let lines = self.lines(text, style);
+        let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let width = (columns * self.advance())
+            .saturating_sub(1)
+            .saturating_mul(style.scale);
+        let height = (lines.len() * self.line_height())
+            .saturating_sub(1)
+            .saturating_mul(style.scale);
+        (width, height)
+    }
+}
+
+/// How text is drawn.
+#[derive(Debug, Copy, Clone)]
+pub struct TextStyle {
+    color: Color<u8>,
+    scale: usize,
+    wrap_width: Option<usize>,
+}
+
+impl TextStyle {
+    /// Text in the color, at the font's own size, with no wrapping.
+    #[inline]
+    #[must_use]
+    pub fn new(color: Color<u8>) -> Self {
+        Self {
+            color,
+            scale: 1,
+            wrap_width: None,
+        }
+    }
+
+    /// Draws each pixel of the font as a square this many pixels wide.
+    #[inline]
+    #[must_use]
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Wraps lines so they are no wider than this many pixels.
+    #[inline]
+    #[must_use]
+    pub fn with_wrap_width(mut self, wrap_width: usize) -> Self {
+        self.wrap_width = Some(wrap_width);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn color(&self) -> Color<u8> {
+        self.color
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn wrap_width(&self) -> Option<usize> {
+        self.wrap_width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmap_font::{BitmapFont, TextStyle};
+    use crate::color::Color;
+
+    #[test]
+    fn test_embedded_font_covers_ascii() {
+        let font = BitmapFont::embedded();
+        assert_eq!((5, 8), (font.glyph_width(), font.glyph_height()));
+        for character in ' '..='~' {
+            assert!(font.glyphs.contains_key(&character), "{character:?}");
+        }
+        assert_eq!(font.glyph('?'), font.glyph('é'));
+    }
+
+    #[test]
+    fn test_parse_rejects_ragged_glyphs() {
+        assert!(BitmapFont::parse("[a]\n#.\n.#\n[b]\n##\n").is_err());
+        assert!(BitmapFont::parse("[a]\n#.\n.x\n").is_err());
+        assert!(BitmapFont::parse("#.\n").is_err());
+        assert!(BitmapFont::parse("[a]\n").is_err());
+        assert!(BitmapFont::parse("[a]\n[b]\n").is_err());
+        assert!(BitmapFont::parse("[a]\n#.\n.#\n[b]\n##\n..\n").is_ok());
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let font = BitmapFont::embedded();
+        // Each character is 6 pixels apart, so 4 fit in 23 pixels.
+        let style = TextStyle::new(Color::default()).with_wrap_width(23);
+        assert_eq!(
+            vec!["one", "two", "thre", "e", "four", "a b"],
+            font.lines("one two\nthree four a b", &style)
+        );
+        assert_eq!((23, 53), font.measure("one two\nthree four a b", &style));
+        let scaled = style.with_scale(2);
+        assert_eq!(vec!["on", "e"], font.lines("one", &scaled));
+        assert_eq!((10, 16), font.measure("x", &scaled));
+        let huge = style.with_scale(usize::MAX);
+        assert_eq!(vec!["o", "n", "e"], font.lines("one", &huge));
+        assert_eq!((usize::MAX, usize::MAX), font.measure("one", &huge));
+    }
+}
@@ -0,0 +1,286 @@
+//! The camera decides which part of the world is drawn. It sits at a world coordinate,
+//! which is drawn in the middle of the view, and follows a target, usually the player,
+//! according to its mode.
+
+use crate::point_2d::Point2d;
+use crate::units::si::length::{Length, pixel};
+use crate::units::si::time::Time;
+use crate::world_coordinate::WorldCoordinate;
+use serde::{Deserialize, Serialize};
+use uom::num::Zero;
+use uom::si::ratio::ratio;
+
+/// How the camera follows its target.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// Keeps the target in the middle of the view.
+    #[default]
+    Locked,
+    /// Stays put while the target is within a box of the given size around the middle
+    /// of the view, moving only as far as it takes to keep the target inside.
+    DeadZone { width: Length, height: Length },
+    /// Eases toward the target, closing half the distance every half-life.
+    Smooth { half_life: Time },
+    /// Shows the middle of the tile map holding the target, jumping to the next tile
+    /// map when the target crosses into it.
+    RoomSnap,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Camera {
+    position: WorldCoordinate,
+    mode: CameraMode,
+    /// The bottom-left and top-right corners the view must stay within.
+    bounds: Option<(WorldCoordinate, WorldCoordinate)>,
+}
+
+impl Camera {
+    /// A locked camera, with no bounds, looking at the position.
+    #[inline]
+    #[must_use]
+    pub fn new(position: WorldCoordinate) -> Self {
+        Self {
+            position,
+            mode: CameraMode::default(),
+            bounds: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_mode(mut self, mode: CameraMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Keeps the view between the bottom-left and top-right corners. When the view is
+    /// larger than the bounds, they are centered in it instead.
+    #[inline]
+    #[must_use]
+    pub fn with_bounds(mut self, bottom_left: WorldCoordinate, top_right: WorldCoordinate) -> Self {
+        self.bounds = Some((bottom_left, top_right));
+        self
+    }
+
+    /// The world coordinate in the middle of the view.
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> &WorldCoordinate {
+        &self.position
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        self.mode = mode;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bounds(&self) -> Option<&(WorldCoordinate, WorldCoordinate)> {
+        self.bounds.as_ref()
+    }
+
+    #[inline]
+    pub fn set_bounds(&mut self, bounds: Option<(WorldCoordinate, WorldCoordinate)>) {
+        self.bounds = bounds;
+    }
+
+    /// Moves straight to where the camera would settle on the target, without easing.
+    pub fn snap_to(&mut self, target: &WorldCoordinate, view_width: f32, view_height: f32) {
+        self.position = target.clone();
+        let goal = self.goal(target);
+        self.position = self.clamped(goal, view_width, view_height);
+    }
+
+    /// Moves the camera after the elapsed time, following the target. When the target
+    /// changes level, the camera snaps to it rather than easing across.
+    pub fn follow(
+        &mut self,
+        target: &WorldCoordinate,
+        view_width: f32,
+        view_height: f32,
+        elapsed: Time,
+    ) {
+        if target.level() != self.position.level() {
+            self.snap_to(target, view_width, view_height);
+            return;
+        }
+        let goal = self.clamped(self.goal(target), view_width, view_height);
+        self.position = match self.mode {
+            CameraMode::Smooth { half_life } if half_life > Time::zero() => {
+                let remaining = 0.5f32.powf((elapsed / half_life).get::<ratio>());
+                let distance = goal.offset_from(&self.position);
+                self.position.shifted(
+                    distance.x() * (1f32 - remaining),
+                    distance.y() * (1f32 - remaining),
+                )
+            }
+            _ => goal,
+        };
+    }
+
+    /// The world coordinate drawn at the bottom-left corner of the view.
+    #[must_use]
+    pub fn view_origin(&self, view_width: f32, view_height: f32) -> WorldCoordinate {
+        self.position
+            .shifted(-view_width / 2f32, -view_height / 2f32)
+    }
+
+    /// Where the coordinate is drawn, in pixels from the bottom-left corner of the view.
+    #[must_use]
+    pub fn project(
+        &self,
+        coordinate: &WorldCoordinate,
+        view_width: f32,
+        view_height: f32,
+    ) -> Point2d<f32> {
+        coordinate.offset_from(&self.view_origin(view_width, view_height))
+    }
+
+    /// Where the camera wants to be for the target, before bounds are applied.
+    fn goal(&self, target: &WorldCoordinate) -> WorldCoordinate {
+        match self.mode {
+            CameraMode::Locked | CameraMode::Smooth { .. } => target.clone(),
+            CameraMode::DeadZone { width, height } => {
+                let distance = target.offset_from(&self.position);
+                let delta_x = Self::excess(distance.x(), width.get::<pixel>() / 2f32);
+                let delta_y = Self::excess(distance.y(), height.get::<pixel>() / 2f32);
+                self.position.shifted(delta_x, delta_y)
+            }
+            CameraMode::RoomSnap => target.tile_map_center(),
+        }
+    }
+
+    /// How far the distance reaches beyond the reach, in either direction.
+    fn excess(distance: f32, reach: f32) -> f32 {
+        if distance > reach {
+            distance - reach
+        } else if distance < -reach {
+            distance + reach
+        } else {
+            0f32
+        }
+    }
+
+    fn clamped(
+        &self,
+        position: WorldCoordinate,
+        view_width: f32,
+        view_height: f32,
+    ) -> WorldCoordinate {
+        let Some((bottom_left, top_right)) = &self.bounds else {
+            return position;
+        };
+        let size = top_right.offset_from(bottom_left);
+        let current = position.offset_from(bottom_left);
+        let delta_x = Self::clamp_axis(current.x(), size.x(), view_width) - current.x();
+        let delta_y = Self::clamp_axis(current.y(), size.y(), view_height) - current.y();
+        position.shifted(delta_x, delta_y)
+    }
+
+    /// Keeps the middle of a view far enough from either end that the view stays within
+    /// the bounds, or centers it if it cannot.
+    fn clamp_axis(middle: f32, size: f32, view: f32) -> f32 {
+        if view >= size {
+            size / 2f32
+        } else {
+            middle.clamp(view / 2f32, size - view / 2f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{Camera, CameraMode};
+    use crate::point_2d::Point2d;
+    use crate::tile_map_coordinate::TileMapCoordinate;
+    use crate::tile_map_key::TileMapKey;
+    use crate::units::si::length::{Length, pixel};
+    use crate::units::si::time::Time;
+    use crate::world::World;
+    use crate::world_coordinate::WorldCoordinate;
+    use uom::si::time::second;
+
+    const TILE_SIZE: f32 = 10f32;
+
+    /// A world of 4x3 tile maps of 10 pixel tiles, with a coordinate at its origin.
+    fn origin() -> WorldCoordinate {
+        let world = World::new(3, 4, Length::new::<pixel>(TILE_SIZE));
+        WorldCoordinate::new(
+            &world,
+            TileMapKey { x: 0, y: 0, z: 0 },
+            TileMapCoordinate::at_x_y(0, 0),
+        )
+    }
+
+    fn assert_near(expected: (f32, f32), actual: Point2d<f32>) {
+        let close = |a: f32, b: f32| (a - b).abs() < 0.01;
+        assert!(
+            close(expected.0, actual.x()) && close(expected.1, actual.y()),
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn test_locked_and_projection() {
+        let origin = origin();
+        let mut camera = Camera::new(origin.clone());
+        let target = origin.shifted(55f32, -12f32);
+        camera.follow(&target, 100f32, 60f32, Time::new::<second>(1f32));
+        assert_near((55f32, -12f32), camera.position().offset_from(&origin));
+        // The target is drawn in the middle of the view.
+        assert_near((50f32, 30f32), camera.project(&target, 100f32, 60f32));
+        assert_near((-5f32, 42f32), camera.project(&origin, 100f32, 60f32));
+    }
+
+    #[test]
+    fn test_dead_zone_and_smooth() {
+        let origin = origin();
+        let dead_zone = CameraMode::DeadZone {
+            width: Length::new::<pixel>(20f32),
+            height: Length::new::<pixel>(10f32),
+        };
+        let mut camera = Camera::new(origin.clone()).with_mode(dead_zone);
+        let elapsed = Time::new::<second>(0.1f32);
+        camera.follow(&origin.shifted(8f32, -4f32), 100f32, 60f32, elapsed);
+        assert_near((0f32, 0f32), camera.position().offset_from(&origin));
+        camera.follow(&origin.shifted(25f32, -4f32), 100f32, 60f32, elapsed);
+        assert_near((15f32, 0f32), camera.position().offset_from(&origin));
+
+        let smooth = CameraMode::Smooth {
+            half_life: Time::new::<second>(0.5f32),
+        };
+        let mut camera = Camera::new(origin.clone()).with_mode(smooth);
+        let target = origin.shifted(40f32, 0f32);
+        camera.follow(&target, 100f32, 60f32, Time::new::<second>(0.5f32));
+        assert_near((20f32, 0f32), camera.position().offset_from(&origin));
+        camera.follow(&target, 100f32, 60f32, Time::new::<second>(1f32));
+        assert_near((35f32, 0f32), camera.position().offset_from(&origin));
+    }
+
+    #[test]
+    fn test_room_snap_and_bounds() {
+        let origin = origin();
+        let mut camera = Camera::new(origin.clone()).with_mode(CameraMode::RoomSnap);
+        // Tile maps are 40x30 pixels, so the target is in the tile map to the east.
+        camera.snap_to(&origin.shifted(47f32, 3f32), 20f32, 20f32);
+        assert_near((60f32, 15f32), camera.position().offset_from(&origin));
+
+        // Two tile maps wide and one high.
+        let top_right = origin.shifted(80f32, 30f32);
+        let mut camera = Camera::new(origin.clone()).with_bounds(origin.clone(), top_right);
+        camera.snap_to(&origin.shifted(5f32, 25f32), 20f32, 20f32);
+        assert_near((10f32, 20f32), camera.position().offset_from(&origin));
+        camera.snap_to(&origin.shifted(200f32, -50f32), 20f32, 20f32);
+        assert_near((70f32, 10f32), camera.position().offset_from(&origin));
+        // Too tall to fit, so the bounds are centered vertically.
+        camera.snap_to(&origin.shifted(30f32, 0f32), 20f32, 50f32);
+        assert_near((30f32, 15f32), camera.position().offset_from(&origin));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::point_2d::Point2d;
+
+/// Where a moving box first touches something it cannot pass through.
+#[derive(Debug, Copy, Clone)]
+pub struct Collision {
+    time: f32,
+    normal: Point2d<f32>,
+}
+
+impl Collision {
+    #[inline]
+    #[must_use]
+    pub fn new(time: f32, normal: Point2d<f32>) -> Self {
+        Self { time, normal }
+    }
+
+    /// How far through the move the box touches, from 0 at the start to 1 at the end.
+    #[inline]
+    #[must_use]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// The direction the face that was hit points, one unit along either axis.
+    #[inline]
+    #[must_use]
+    pub fn normal(&self) -> Point2d<f32> {
+        self.normal
+    }
+}
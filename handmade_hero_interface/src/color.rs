@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Color<T> {
     blue: T,
     green: T,
@@ -36,19 +36,75 @@ impl<T> Color<T> {
 }
 
 impl Color<u8> {
+    /// An opaque color.
     #[must_use]
     #[inline]
     pub fn from_rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::from_rgba(red, green, blue, u8::MAX)
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn from_rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
         Self {
-            red,
-            green,
             blue,
-            alpha: 0,
+            green,
+            red,
+            alpha,
         }
     }
+
+    /// The color with its red, green and blue scaled by its alpha.
+    #[must_use]
+    pub fn premultiplied(self) -> Self {
+        Self::from(Color::<f32>::from(self).premultiplied())
+    }
+
+    /// Reverses `premultiplied`, as closely as 8-bit channels allow.
+    #[must_use]
+    pub fn unpremultiplied(self) -> Self {
+        Self::from(Color::<f32>::from(self).unpremultiplied())
+    }
 }
 
 impl Color<f32> {
+    #[inline]
+    fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            blue,
+            green,
+            red,
+            alpha,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn premultiplied(self) -> Self {
+        Self::new(
+            self.red * self.alpha,
+            self.green * self.alpha,
+            self.blue * self.alpha,
+            self.alpha,
+        )
+    }
+
+    /// Reverses `premultiplied`. A fully transparent color has no color left to
+    /// recover, so it becomes transparent black.
+    #[must_use]
+    #[inline]
+    pub fn unpremultiplied(self) -> Self {
+        if self.alpha <= 0f32 {
+            return Self::default();
+        }
+        Self::new(
+            self.red / self.alpha,
+            self.green / self.alpha,
+            self.blue / self.alpha,
+            self.alpha,
+        )
+    }
+
     #[inline]
     fn convert_to_f32(component: u8) -> f32 {
         f32::from(component) / f32::from(u8::MAX)
@@ -58,7 +114,68 @@ impl Color<f32> {
     #[allow(clippy::cast_sign_loss)]
     #[allow(clippy::cast_possible_truncation)]
     fn convert_to_u8(component: f32) -> u8 {
-        (component * f32::from(u8::MAX)) as u8
+        // Rounding, rather than truncating, makes u8 -> f32 -> u8 lossless.
+        (component.clamp(0f32, 1f32) * f32::from(u8::MAX)).round() as u8
+    }
+
+    fn map(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self::new(
+            f(self.red, other.red),
+            f(self.green, other.green),
+            f(self.blue, other.blue),
+            f(self.alpha, other.alpha),
+        )
+    }
+}
+
+/// How a color being drawn combines with the color already in the target.
+///
+/// Colors are stored with straight (not premultiplied) alpha. The blend itself is done
+/// on premultiplied colors, so transparent pixels never bleed their color into the
+/// result.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Overwrites the destination, alpha included.
+    Replace,
+    /// Draws the source on top of the destination, showing through where translucent.
+    #[default]
+    Over,
+    /// Adds the source to the destination, brightening it, e.g. for glows.
+    Additive,
+    /// Multiplies the destination by the source, darkening it, e.g. for shadows.
+    Multiply,
+}
+
+impl BlendMode {
+    #[must_use]
+    pub fn blend(self, source: Color<u8>, destination: Color<u8>) -> Color<u8> {
+        match self {
+            Self::Replace => return source,
+            Self::Over if source.alpha == u8::MAX => return source,
+            Self::Over | Self::Additive | Self::Multiply if source.alpha == 0 => {
+                return destination;
+            }
+            _ => {}
+        }
+        let source = Color::<f32>::from(source).premultiplied();
+        let destination = Color::<f32>::from(destination).premultiplied();
+        let blended = match self {
+            Self::Replace => source,
+            Self::Over => source.map(destination, |s, d| s + d * (1f32 - source.alpha)),
+            Self::Additive => {
+                let mut sum = source.map(destination, |s, d| s + d);
+                sum.alpha = sum.alpha.min(1f32);
+                sum
+            }
+            Self::Multiply => {
+                let mut product = source.map(destination, |s, d| {
+                    s * d + s * (1f32 - destination.alpha) + d * (1f32 - source.alpha)
+                });
+                product.alpha = source.alpha + destination.alpha * (1f32 - source.alpha);
+                product
+            }
+        };
+        Color::from(blended.unpremultiplied())
     }
 }
 
@@ -75,10 +192,11 @@ impl From<Color<u8>> for u32 {
 impl From<Color<f32>> for Color<u8> {
     #[inline]
     fn from(value: Color<f32>) -> Self {
-        Color::from_rgb(
+        Color::from_rgba(
             Color::convert_to_u8(value.red),
             Color::convert_to_u8(value.green),
             Color::convert_to_u8(value.blue),
+            Color::convert_to_u8(value.alpha),
         )
     }
 }
@@ -94,3 +212,57 @@ impl From<Color<u8>> for Color<f32> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{BlendMode, Color};
+
+    #[test]
+    fn test_conversions_are_lossless() {
+        for value in 0..=u8::MAX {
+            let color = Color::from_rgba(value, value, value, value);
+            assert_eq!(color, Color::<u8>::from(Color::<f32>::from(color)));
+        }
+        assert_eq!(u8::MAX, Color::from_rgb(1, 2, 3).alpha());
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let color = Color::from_rgba(0xFF, 0x80, 0x00, 0x80);
+        let premultiplied = color.premultiplied();
+        assert_eq!(Color::from_rgba(0x80, 0x40, 0x00, 0x80), premultiplied);
+        assert_eq!(color, premultiplied.unpremultiplied());
+        let clear = Color::from_rgba(0xFF, 0xFF, 0xFF, 0);
+        assert_eq!(Color::default(), clear.premultiplied().unpremultiplied());
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let grey = Color::from_rgb(0x80, 0x80, 0x80);
+        let translucent_white = Color::from_rgba(0xFF, 0xFF, 0xFF, 0x80);
+        let clear = Color::from_rgba(0xFF, 0x00, 0x00, 0);
+
+        assert_eq!(
+            translucent_white,
+            BlendMode::Replace.blend(translucent_white, grey)
+        );
+        assert_eq!(grey, BlendMode::Over.blend(clear, grey));
+        assert_eq!(
+            Color::from_rgb(0xC0, 0xC0, 0xC0),
+            BlendMode::Over.blend(translucent_white, grey)
+        );
+        // A translucent color drawn over nothing stays translucent.
+        assert_eq!(
+            translucent_white,
+            BlendMode::Over.blend(translucent_white, Color::default())
+        );
+        assert_eq!(
+            Color::from_rgb(0xFF, 0xFF, 0xFF),
+            BlendMode::Additive.blend(translucent_white, grey)
+        );
+        assert_eq!(
+            Color::from_rgb(0x40, 0x80, 0x80),
+            BlendMode::Multiply.blend(Color::from_rgba(0x00, 0xFF, 0xFF, 0x80), grey)
+        );
+    }
+}
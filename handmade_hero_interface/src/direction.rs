@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// The way something faces or moves, as seen on screen.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    #[default]
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Down, Self::Left, Self::Right, Self::Up];
+
+    /// The direction of the larger component of a movement, with y increasing upward.
+    /// Diagonal movement favors left and right. `None` is returned if there is no
+    /// movement.
+    #[must_use]
+    pub fn from_delta(delta_x: f32, delta_y: f32) -> Option<Self> {
+        if delta_x == 0f32 && delta_y == 0f32 {
+            None
+        } else if delta_x.abs() >= delta_y.abs() {
+            Some(if delta_x > 0f32 {
+                Self::Right
+            } else {
+                Self::Left
+            })
+        } else {
+            Some(if delta_y > 0f32 { Self::Up } else { Self::Down })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::Direction;
+
+    #[test]
+    fn test_from_delta() {
+        assert_eq!(None, Direction::from_delta(0f32, 0f32));
+        assert_eq!(Some(Direction::Up), Direction::from_delta(0.5f32, 1f32));
+        assert_eq!(Some(Direction::Down), Direction::from_delta(0f32, -1f32));
+        assert_eq!(Some(Direction::Left), Direction::from_delta(-1f32, 1f32));
+        assert_eq!(Some(Direction::Right), Direction::from_delta(1f32, -0.5f32));
+    }
+}
@@ -1,11 +1,12 @@
+use crate::camera::Camera;
 use crate::player::Player;
+use crate::random::Random;
 use crate::sound_state::SoundState;
 use crate::tile_map_key::TileMapKey;
 use crate::units::si::length::Length;
 use crate::units::si::time::Time;
 use crate::world::World;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use uom::num::Zero;
 use uom::si::length::meter;
 
@@ -17,26 +18,24 @@ pub struct GameState {
     player: Player,
     frame_duration: Time,
     world: World,
+    camera: Camera,
+    random: Random,
 }
 
 impl GameState {
+    /// The seed a new game's random numbers are drawn from, so every game plays out the
+    /// same given the same input.
+    pub const DEFAULT_SEED: u64 = 0x4841_4E44_4D41_4445;
+
     #[inline]
     #[must_use]
     pub fn new() -> Self {
         let sound = SoundState::new();
         let tile_size = Length::new::<meter>(1.4f32);
-        let x_offset = -(tile_size / 1.6f32);
-        let y_offset = -(tile_size / 3.5f32);
-        let world = World {
-            rows: World::TILE_ROWS,
-            columns: World::TILE_COLUMNS,
-            x_offset,
-            y_offset,
-            tile_maps: HashMap::new(),
-            tile_size,
-        };
-        let current_tile_map_key = TileMapKey { x: 0, y: 0 };
+        let world = World::new(World::TILE_ROWS, World::TILE_COLUMNS, tile_size);
+        let current_tile_map_key = TileMapKey { x: 0, y: 0, z: 0 };
         let player = Player::new(&world, current_tile_map_key);
+        let camera = Camera::new(player.coordinate().clone());
         Self {
             width: Length::zero(),
             height: Length::zero(),
@@ -44,6 +43,8 @@ impl GameState {
             player,
             frame_duration: Time::zero(),
             world,
+            camera,
+            random: Random::new(Self::DEFAULT_SEED),
         }
     }
 
@@ -115,6 +116,26 @@ impl GameState {
     pub fn world_mut(&mut self) -> &mut World {
         &mut self.world
     }
+
+    #[inline]
+    #[must_use]
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// The only source of random numbers the game should use, since it is saved with the
+    /// rest of the state and so replays the same way.
+    #[inline]
+    #[must_use]
+    pub fn random_mut(&mut self) -> &mut Random {
+        &mut self.random
+    }
 }
 
 impl Default for GameState {
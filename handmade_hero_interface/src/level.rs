@@ -0,0 +1,287 @@
+//! Levels describe the tile maps that make up a world, so they can be edited without
+//! rebuilding the game.
+//!
+//! The text format lists tile maps one after another. Each starts with a header giving
+//! its tile map key and size, followed by one line of tile ids per row, top row first:
+//!
+//! ```text
+//! # A small room at the origin, with a door to the east.
+//! tile_map 0 0 0 4 3
+//! 1 1 1 1
+//! 1 0 0 0
+//! 1 1 1 1
+//! ```
+//!
+//! The header reads `tile_map <x> <y> <z> <columns> <rows>`. Blank lines and lines
+//! starting with `#` are ignored.
+
+use crate::application_error::{ApplicationError, ErrorKind, Result};
+use crate::tile_map_coordinate::TileMapCoordinate;
+use crate::tile_map_key::TileMapKey;
+use crate::world::World;
+use crate::world_coordinate::WorldCoordinate;
+use std::path::Path;
+use std::str::FromStr;
+
+const TILE_MAP_HEADER: &str = "tile_map";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Level {
+    tile_maps: Vec<LevelTileMap>,
+}
+
+impl Level {
+    #[inline]
+    #[must_use]
+    pub fn new(tile_maps: Vec<LevelTileMap>) -> Self {
+        Self { tile_maps }
+    }
+
+    /// # Errors
+    /// An error is returned if the file cannot be read. An `ErrorKind::MalformedLevel`
+    /// error is returned if its contents are malformed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            ApplicationError::wrap(format!("Could not read the level {}", path.display()), e)
+        })?;
+        Self::parse(&source)
+    }
+
+    /// # Errors
+    /// An `ErrorKind::MalformedLevel` error is returned if a header or row is malformed,
+    /// or a tile map does not have as many rows and columns as its header says.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+        let mut tile_maps = Vec::new();
+        while let Some((number, header)) = lines.next() {
+            let (key, columns, rows) = Self::parse_header(number, header)?;
+            if columns.checked_mul(rows).is_none() {
+                return Err(malformed(format!(
+                    "Line {number}: a {columns}x{rows} tile map is too large"
+                )));
+            }
+            // Don't trust the header's size until the rows are actually there.
+            let mut tiles = Vec::new();
+            for row in 0..rows {
+                let Some((number, line)) = lines.next_if(|(_, l)| !l.starts_with(TILE_MAP_HEADER))
+                else {
+                    return Err(malformed(format!(
+                        "Line {number}: the tile map has {row} rows but should have {rows}"
+                    )));
+                };
+                let row = line
+                    .split_whitespace()
+                    .map(|id| parse_number::<u32>(number, id))
+                    .collect::<Result<Vec<_>>>()?;
+                if row.len() != columns {
+                    return Err(malformed(format!(
+                        "Line {number}: the row has {} tiles but should have {columns}",
+                        row.len()
+                    )));
+                }
+                tiles.push(row);
+            }
+            // The file lists the top row first, but rows are counted from the bottom up.
+            tiles.reverse();
+            tile_maps.push(LevelTileMap::new(key, columns, rows, tiles.concat())?);
+        }
+        Ok(Self { tile_maps })
+    }
+
+    fn parse_header(number: usize, header: &str) -> Result<(TileMapKey, usize, usize)> {
+        let mut parts = header.split_whitespace();
+        if parts.next() != Some(TILE_MAP_HEADER) {
+            return Err(malformed(format!(
+                "Line {number}: expected a tile map header, but found {header:?}"
+            )));
+        }
+        let parts = parts.collect::<Vec<_>>();
+        let [x, y, z, columns, rows] = parts.as_slice() else {
+            return Err(malformed(format!(
+                "Line {number}: the header should read \"{TILE_MAP_HEADER} <x> <y> <z> <columns> <rows>\""
+            )));
+        };
+        let key = TileMapKey {
+            x: parse_number(number, x)?,
+            y: parse_number(number, y)?,
+            z: parse_number(number, z)?,
+        };
+        Ok((
+            key,
+            parse_number(number, columns)?,
+            parse_number(number, rows)?,
+        ))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn tile_maps(&self) -> &[LevelTileMap] {
+        &self.tile_maps
+    }
+
+    /// Sets the tiles of every tile map in the world. Each tile map's bottom-left tile is
+    /// placed at the bottom-left corner of the world's tile map with the same key, though
+    /// it may be a different size.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn populate(&self, world: &mut World) {
+        for tile_map in &self.tile_maps {
+            let origin = TileMapCoordinate::at_x_y(0, 0);
+            let origin = WorldCoordinate::new(world, tile_map.key, origin).tile();
+            for row in 0..tile_map.rows {
+                for column in 0..tile_map.columns {
+                    let tile = origin.shifted(column as i32, row as i32);
+                    world.set_tile(tile, tile_map.tile(column, row));
+                }
+            }
+        }
+    }
+}
+
+/// One tile map of a level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelTileMap {
+    key: TileMapKey,
+    columns: usize,
+    rows: usize,
+    /// Tile ids, bottom row first.
+    tiles: Vec<u32>,
+}
+
+impl LevelTileMap {
+    /// Creates a tile map from its tile ids, listed bottom row first.
+    ///
+    /// # Errors
+    /// An `ErrorKind::MalformedLevel` error is returned if there is not one tile for
+    /// each row and column.
+    pub fn new(key: TileMapKey, columns: usize, rows: usize, tiles: Vec<u32>) -> Result<Self> {
+        if columns.checked_mul(rows) != Some(tiles.len()) {
+            return Err(malformed(format!(
+                "A {columns}x{rows} tile map cannot hold {} tiles",
+                tiles.len()
+            )));
+        }
+        Ok(Self {
+            key,
+            columns,
+            rows,
+            tiles,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> TileMapKey {
+        self.key
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The id of the tile, with rows counted from the bottom.
+    ///
+    /// # Panics
+    /// Panics if the column or row is outside the tile map.
+    #[inline]
+    #[must_use]
+    pub fn tile(&self, column: usize, row: usize) -> u32 {
+        assert!(column < self.columns, "The column is outside the tile map");
+        self.tiles[row * self.columns + column]
+    }
+}
+
+fn parse_number<T: FromStr>(number: usize, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| malformed(format!("Line {number}: {value:?} is not a valid number")))
+}
+
+fn malformed(message: impl Into<String>) -> ApplicationError {
+    ApplicationError::new(message).with_kind(ErrorKind::MalformedLevel)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application_error::ErrorKind;
+    use crate::level::Level;
+    use crate::tile_coordinate::TileCoordinate;
+    use crate::tile_map_key::TileMapKey;
+    use crate::units::si::length::{Length, pixel};
+    use crate::world::World;
+
+    const SOURCE: &str = "
+        # A room with a door to the east.
+        tile_map 0 0 0 4 3
+        1 1 1 1
+        1 0 0 0
+
+        1 1 1 2
+        tile_map -1 0 1 2 1
+        3 0
+    ";
+
+    #[test]
+    fn test_parse() {
+        let level = Level::parse(SOURCE).unwrap();
+        let [room, upstairs] = level.tile_maps() else {
+            panic!("Expected two tile maps");
+        };
+        assert_eq!(TileMapKey { x: 0, y: 0, z: 0 }, room.key());
+        assert_eq!((4, 3), (room.columns(), room.rows()));
+        // The bottom row comes last in the file.
+        assert_eq!(2, room.tile(3, 0));
+        assert_eq!(0, room.tile(3, 1));
+        assert_eq!(1, room.tile(0, 2));
+        assert_eq!(TileMapKey { x: -1, y: 0, z: 1 }, upstairs.key());
+        assert_eq!(3, upstairs.tile(0, 0));
+    }
+
+    #[test]
+    fn test_malformed() {
+        for source in [
+            "tile_map 0 0 0 2 2\n1 1\n",
+            "tile_map 0 0 0 2 1\n1 1 1\n",
+            "tile_map 0 0 0 2 1\n1 x\n",
+            "tile_map 0 0 2 1\n1 1\n",
+            "tile_map 0 0 0 2 2\n1 1\ntile_map 1 0 0 2 1\n1 1\n",
+            "1 1\n",
+            "tile_map 0 0 0 18446744073709551615 2\n1 1\n",
+            "tile_map 0 0 0 4294967296 4294967296\n1 1\n",
+        ] {
+            let error = Level::parse(source).unwrap_err();
+            assert_eq!(ErrorKind::MalformedLevel, error.kind(), "{source:?}");
+        }
+        assert!(
+            Level::parse("# Nothing here\n")
+                .unwrap()
+                .tile_maps()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_populate() {
+        let mut world = World::new(3, 4, Length::new::<pixel>(10f32));
+        Level::parse(SOURCE).unwrap().populate(&mut world);
+        assert_eq!(Some(2), world.get_tile(TileCoordinate::new(3, 0, 0)));
+        assert_eq!(Some(1), world.get_tile(TileCoordinate::new(0, 2, 0)));
+        // The tile map to the west starts four columns to the left.
+        assert_eq!(Some(3), world.get_tile(TileCoordinate::new(-4, 0, 1)));
+        assert_eq!(Some(0), world.get_tile(TileCoordinate::new(-3, 0, 1)));
+        assert_eq!(None, world.get_tile(TileCoordinate::new(-2, 0, 1)));
+    }
+}
@@ -1,27 +1,45 @@
 #[macro_use]
 extern crate uom;
 
+pub mod animation_clip;
 pub mod application;
 pub mod application_error;
 pub mod audio_context;
+pub mod bitmap;
+pub mod bitmap_font;
 pub mod button_state;
+pub mod camera;
+pub mod collision;
 pub mod color;
 pub mod controller_state;
+pub mod direction;
 pub mod game_state;
 pub mod initialize_context;
 pub mod input_context;
 pub mod input_state;
 pub mod joystick_state;
+pub mod level;
 pub mod mouse_state;
+pub mod platform_status;
 pub mod player;
+pub mod plugin_abi;
 pub mod point_2d;
+pub mod random;
+pub mod rasterizer;
 pub mod rectangle;
 pub mod render_context;
+pub mod render_target;
 pub mod sound_state;
+pub mod sprite_sheet;
 pub mod stereo_sample;
-pub mod tile_map;
+pub mod tile_coordinate;
+pub mod tile_definition;
 pub mod tile_map_coordinate;
 pub mod tile_map_key;
+pub mod tile_registry;
+pub mod tile_store;
+pub mod tiled;
 pub mod units;
 pub mod world;
 pub mod world_coordinate;
+pub mod world_generator;
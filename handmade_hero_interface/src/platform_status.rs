@@ -0,0 +1,39 @@
+use crate::units::si::time::Time;
+use uom::num::Zero;
+
+/// What the platform layer is doing, handed to the game each frame so it can be shown
+/// on screen. None of it is part of the game state, so none of it is recorded.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlatformStatus {
+    /// How long the previous frame took, not counting the time spent waiting for the
+    /// next one to start.
+    pub frame_time: Time,
+    pub recording: RecordingStatus,
+}
+
+impl Default for PlatformStatus {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            frame_time: Time::zero(),
+            recording: RecordingStatus::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum RecordingStatus {
+    #[default]
+    Idle,
+    Recording {
+        slot: usize,
+        frame_count: u64,
+    },
+    /// Playing back a recording, from a slot or from a file given some other way.
+    Playing {
+        slot: Option<usize>,
+        position: u64,
+        frame_count: u64,
+        paused: bool,
+    },
+}
@@ -1,11 +1,14 @@
 use crate::color::Color;
+use crate::direction::Direction;
 use crate::rectangle::Rectangle;
 use crate::tile_map_coordinate::TileMapCoordinate;
 use crate::tile_map_key::TileMapKey;
 use crate::units::si::length::pixel;
+use crate::units::si::time::Time;
 use crate::world::World;
 use crate::world_coordinate::WorldCoordinate;
 use serde::{Deserialize, Serialize};
+use uom::num::Zero;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Player {
@@ -13,6 +16,8 @@ pub struct Player {
     height: f32,
     width: f32,
     color: Color<f32>,
+    facing: Direction,
+    walk_duration: Time,
 }
 
 impl Player {
@@ -28,6 +33,8 @@ impl Player {
             height,
             width,
             color,
+            facing: Direction::default(),
+            walk_duration: Time::zero(),
         }
     }
 
@@ -53,6 +60,33 @@ impl Player {
         self.color
     }
 
+    #[inline]
+    #[must_use]
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+
+    /// How long the player has been walking without stopping, which drives the walk
+    /// animation. Zero while standing still.
+    #[inline]
+    #[must_use]
+    pub fn walk_duration(&self) -> Time {
+        self.walk_duration
+    }
+
+    /// Turns the player to face the direction and continues walking for the duration.
+    #[inline]
+    pub fn walk(&mut self, direction: Direction, duration: Time) {
+        self.facing = direction;
+        self.walk_duration += duration;
+    }
+
+    /// Stops walking, keeping the direction the player faces.
+    #[inline]
+    pub fn stand(&mut self) {
+        self.walk_duration = Time::zero();
+    }
+
     #[inline]
     #[must_use]
     pub fn tile_map_key(&self) -> TileMapKey {
@@ -0,0 +1,197 @@
+use crate::application_error::{ApplicationError, ErrorKind, Result};
+use crate::audio_context::AudioContext;
+use crate::color::Color;
+use crate::game_state::GameState;
+use crate::initialize_context::InitializeContext;
+use crate::input_context::InputContext;
+use crate::input_state::InputState;
+use crate::player::Player;
+use crate::render_context::RenderContext;
+use crate::sound_state::SoundState;
+use crate::stereo_sample::StereoSample;
+use crate::world::World;
+
+/// Bump this whenever the `Application` trait, the way hosts and plugins share state, or
+/// anything serialized with the `GameState` changes. The layout hash only catches some of
+/// these (see `PluginAbi::game_state_layout_hash`), and recordings rely on this version
+/// to refuse game states they can no longer decode.
+pub const INTERFACE_VERSION: u32 = 2;
+
+const RUSTC_VERSION: &str = env!("HANDMADE_HERO_RUSTC_VERSION");
+const RUSTC_VERSION_CAPACITY: usize = 64;
+
+/// Describes the interface a plugin was compiled against. Plugins export a function
+/// returning their descriptor alongside `create_application`, and hosts refuse to
+/// call into any plugin whose descriptor differs from their own.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PluginAbi {
+    interface_version: u32,
+    game_state_layout_hash: u64,
+    rustc_version: [u8; RUSTC_VERSION_CAPACITY],
+}
+
+impl PluginAbi {
+    /// The descriptor of the interface crate as it was compiled into the caller.
+    #[inline]
+    #[must_use]
+    pub const fn current() -> Self {
+        Self {
+            interface_version: INTERFACE_VERSION,
+            game_state_layout_hash: Self::layout_hash(),
+            rustc_version: Self::encode_rustc_version(RUSTC_VERSION),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn interface_version(&self) -> u32 {
+        self.interface_version
+    }
+
+    /// A hash of the size and alignment of the types shared between hosts and plugins.
+    /// It only changes when one of those types changes size or alignment, so it does not
+    /// notice reordered fields, a field swapped for another of the same size, or changes
+    /// to anything behind a pointer, such as the contents of a `Vec` or `HashMap`. Those
+    /// need `INTERFACE_VERSION` to be bumped instead.
+    #[inline]
+    #[must_use]
+    pub fn game_state_layout_hash(&self) -> u64 {
+        self.game_state_layout_hash
+    }
+
+    #[must_use]
+    pub fn rustc_version(&self) -> &str {
+        let length = self
+            .rustc_version
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(RUSTC_VERSION_CAPACITY);
+        std::str::from_utf8(&self.rustc_version[..length]).unwrap_or("unknown")
+    }
+
+    /// # Errors
+    /// An `ErrorKind::IncompatiblePlugin` error is returned describing the first part
+    /// of the descriptor that does not match the host's.
+    pub fn ensure_compatible_with(&self, host: &PluginAbi) -> Result<()> {
+        let message = if self.interface_version != host.interface_version {
+            format!(
+                "The plugin was built against interface version {} but the host expects {}",
+                self.interface_version, host.interface_version
+            )
+        } else if self.rustc_version != host.rustc_version {
+            format!(
+                "The plugin was built with {} but the host was built with {}",
+                self.rustc_version(),
+                host.rustc_version()
+            )
+        } else if self.game_state_layout_hash != host.game_state_layout_hash {
+            format!(
+                "The plugin's game state layout ({:#018x}) does not match the host's ({:#018x})",
+                self.game_state_layout_hash, host.game_state_layout_hash
+            )
+        } else {
+            return Ok(());
+        };
+        Err(ApplicationError::new(message).with_kind(ErrorKind::IncompatiblePlugin))
+    }
+
+    const fn layout_hash() -> u64 {
+        // FNV-1a over the size and alignment of every type shared between the host
+        // and the plugin. This is only a safety net; see `game_state_layout_hash`.
+        let layouts = [
+            (size_of::<GameState>(), align_of::<GameState>()),
+            (size_of::<InputState>(), align_of::<InputState>()),
+            (size_of::<SoundState>(), align_of::<SoundState>()),
+            (size_of::<Player>(), align_of::<Player>()),
+            (size_of::<World>(), align_of::<World>()),
+            (size_of::<Color<u8>>(), align_of::<Color<u8>>()),
+            (size_of::<StereoSample>(), align_of::<StereoSample>()),
+            (
+                size_of::<InitializeContext<'_>>(),
+                align_of::<InitializeContext<'_>>(),
+            ),
+            (
+                size_of::<InputContext<'_>>(),
+                align_of::<InputContext<'_>>(),
+            ),
+            (
+                size_of::<RenderContext<'_>>(),
+                align_of::<RenderContext<'_>>(),
+            ),
+            (
+                size_of::<AudioContext<'_>>(),
+                align_of::<AudioContext<'_>>(),
+            ),
+        ];
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut index = 0;
+        while index < layouts.len() {
+            let (size, align) = layouts[index];
+            hash = (hash ^ size as u64).wrapping_mul(0x0100_0000_01b3);
+            hash = (hash ^ align as u64).wrapping_mul(0x0100_0000_01b3);
+            index += 1;
+        }
+        hash
+    }
+
+    const fn encode_rustc_version(version: &str) -> [u8; RUSTC_VERSION_CAPACITY] {
+        // Leave room for at least one null terminator.
+        let bytes = version.as_bytes();
+        let mut encoded = [0u8; RUSTC_VERSION_CAPACITY];
+        let mut index = 0;
+        while index < bytes.len() && index < RUSTC_VERSION_CAPACITY - 1 {
+            encoded[index] = bytes[index];
+            index += 1;
+        }
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application_error::ErrorKind;
+    use crate::plugin_abi::{INTERFACE_VERSION, PluginAbi};
+
+    #[test]
+    fn test_current_is_compatible_with_itself() {
+        let abi = PluginAbi::current();
+        assert!(abi.ensure_compatible_with(&PluginAbi::current()).is_ok());
+        assert_eq!(INTERFACE_VERSION, abi.interface_version());
+        assert!(abi.rustc_version().starts_with("rustc"));
+    }
+
+    #[test]
+    fn test_interface_version_mismatch() {
+        let host = PluginAbi::current();
+        let plugin = PluginAbi {
+            interface_version: INTERFACE_VERSION + 1,
+            ..host
+        };
+        let error = plugin.ensure_compatible_with(&host).unwrap_err();
+        assert_eq!(ErrorKind::IncompatiblePlugin, error.kind());
+    }
+
+    #[test]
+    fn test_layout_hash_mismatch() {
+        let host = PluginAbi::current();
+        let plugin = PluginAbi {
+            game_state_layout_hash: host.game_state_layout_hash() ^ 1,
+            ..host
+        };
+        let error = plugin.ensure_compatible_with(&host).unwrap_err();
+        assert_eq!(ErrorKind::IncompatiblePlugin, error.kind());
+    }
+
+    #[test]
+    fn test_rustc_version_mismatch() {
+        let host = PluginAbi::current();
+        let plugin = PluginAbi {
+            rustc_version: PluginAbi::encode_rustc_version("rustc 0.0.0"),
+            ..host
+        };
+        let error = plugin.ensure_compatible_with(&host).unwrap_err();
+        assert_eq!(ErrorKind::IncompatiblePlugin, error.kind());
+        assert_eq!("rustc 0.0.0", plugin.rustc_version());
+    }
+}
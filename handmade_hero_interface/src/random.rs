@@ -0,0 +1,175 @@
+//! A small, fast pseudo-random number generator (PCG32). The same seed always produces
+//! the same numbers, on every platform, so anything generated from a seed can be
+//! generated again. Its whole state is serialized with it, so a game saved or recorded
+//! part way through goes on to draw the same numbers when it is loaded.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+const INCREMENT: u64 = 1_442_695_040_888_963_407;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        let mut random = Self { state: 0 };
+        random.next_u32();
+        random.state = random.state.wrapping_add(seed);
+        random.next_u32();
+        random
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    /// A number from zero up to, but not including, the bound, with every number equally
+    /// likely.
+    ///
+    /// # Panics
+    /// Panics if the bound is zero.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0, "The bound must be greater than zero");
+        // Reject the numbers that would make the low remainders more likely.
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return value % bound;
+            }
+        }
+    }
+
+    /// A number in the range, with every number equally likely.
+    ///
+    /// # Panics
+    /// Panics if the range is empty.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn range(&mut self, range: Range<i32>) -> i32 {
+        assert!(!range.is_empty(), "The range must not be empty");
+        let span = range.end.abs_diff(range.start);
+        range.start.wrapping_add(self.below(span) as i32)
+    }
+
+    /// A number from zero up to, but not including, one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn float(&mut self) -> f32 {
+        // Use as many bits as an f32 can hold exactly, so every value is equally likely.
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// A number in the range, which must not be empty.
+    pub fn float_range(&mut self, range: Range<f32>) -> f32 {
+        debug_assert!(!range.is_empty(), "The range must not be empty");
+        (range.start + self.float() * (range.end - range.start)).min(range.end.next_down())
+    }
+
+    /// One of the items, each equally likely, or `None` if there are none.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        let index = self.below(u32::try_from(items.len()).unwrap_or(u32::MAX));
+        items.get(index as usize)
+    }
+
+    /// Puts the items in a random order, with every order equally likely.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.below(u32::try_from(index + 1).unwrap_or(u32::MAX)) as usize;
+            items.swap(index, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::Random;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        let mut other = Random::new(43);
+        let numbers = (0..8).map(|_| first.next_u32()).collect::<Vec<_>>();
+        assert_eq!(
+            numbers,
+            (0..8).map(|_| second.next_u32()).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            numbers,
+            (0..8).map(|_| other.next_u32()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_range_and_shuffle() {
+        let mut random = Random::new(7);
+        let mut seen = [false; 5];
+        for _ in 0..200 {
+            let value = random.range(-2..3);
+            assert!((-2..3).contains(&value));
+            seen[usize::try_from(value + 2).unwrap()] = true;
+        }
+        assert_eq!([true; 5], seen);
+        assert_eq!(i32::MIN, random.range(i32::MIN..i32::MIN + 1));
+
+        let mut items = (0..10).collect::<Vec<_>>();
+        random.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort_unstable();
+        assert_eq!((0..10).collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn test_float_and_choose() {
+        let mut random = Random::new(3);
+        for _ in 0..200 {
+            let value = random.float();
+            assert!((0f32..1f32).contains(&value));
+            let value = random.float_range(-1.5f32..2.5f32);
+            assert!((-1.5f32..2.5f32).contains(&value));
+        }
+        let items = ["a", "b", "c"];
+        let mut seen = Vec::new();
+        for _ in 0..100 {
+            let item = random.choose(&items).unwrap();
+            if !seen.contains(item) {
+                seen.push(item);
+            }
+        }
+        assert_eq!(3, seen.len());
+        assert_eq!(None, random.choose::<u8>(&[]));
+    }
+
+    #[test]
+    fn test_serialized_state_draws_the_same_numbers() {
+        let config = bincode::config::standard();
+        let mut random = Random::new(11);
+        random.next_u64();
+        let bytes = bincode::serde::encode_to_vec(&random, config).unwrap();
+        let (mut restored, _): (Random, usize) =
+            bincode::serde::decode_from_slice(&bytes, config).unwrap();
+        assert_eq!(random, restored);
+        for _ in 0..8 {
+            assert_eq!(random.next_u32(), restored.next_u32());
+        }
+    }
+}
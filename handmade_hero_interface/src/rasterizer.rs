@@ -0,0 +1,663 @@
+use crate::bitmap::Bitmap;
+use crate::bitmap_font::{BitmapFont, TextStyle};
+use crate::color::{BlendMode, Color};
+use crate::point_2d::Point2d;
+use crate::rectangle::Rectangle;
+use crate::render_target::{RenderTarget, blend_span};
+use crate::sprite_sheet::SpriteSheet;
+
+/// Draws lines and shapes into a render target, clipping anything outside of it.
+///
+/// Points are pixel coordinates measured from the bottom-left of the target and may lie
+/// off the target. Outlines pass through the pixels at their vertices. Filled shapes
+/// cover the pixels whose centers fall inside them, using the same half-open convention
+/// as `Rectangle`, so a square from (0, 0) to (4, 4) covers 16 pixels and shapes that
+/// share an edge never overlap.
+///
+/// Colors are blended into the target using the blend mode, which is `BlendMode::Over`
+/// unless changed.
+#[derive(Debug)]
+pub struct Rasterizer<'t, 'a> {
+    target: &'t mut RenderTarget<'a>,
+    blend_mode: BlendMode,
+}
+
+impl<'t, 'a> Rasterizer<'t, 'a> {
+    #[inline]
+    #[must_use]
+    pub fn new(target: &'t mut RenderTarget<'a>) -> Self {
+        Self {
+            target,
+            blend_mode: BlendMode::default(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> isize {
+        isize::try_from(self.target.width()).unwrap_or(isize::MAX)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> isize {
+        isize::try_from(self.target.height()).unwrap_or(isize::MAX)
+    }
+
+    /// Blends a single pixel, if it lies within the target.
+    pub fn plot(&mut self, point: Point2d<isize>, color: Color<u8>) {
+        let (Ok(x), Ok(y)) = (usize::try_from(point.x()), usize::try_from(point.y())) else {
+            return;
+        };
+        if let Some(pixel) = self.target.pixel_mut(x, y) {
+            *pixel = self.blend_mode.blend(color, *pixel);
+        }
+    }
+
+    /// Draws a line between two points, including both ends, using Bresenham's algorithm.
+    /// The line is clipped to the target first, so only visible pixels are stepped over.
+    pub fn line(&mut self, from: Point2d<isize>, to: Point2d<isize>, color: Color<u8>) {
+        let Some((from, to)) = self.clip_line(from, to) else {
+            return;
+        };
+        let delta_x = (to.x() - from.x()).abs();
+        let delta_y = -(to.y() - from.y()).abs();
+        let step_x = if from.x() < to.x() { 1 } else { -1 };
+        let step_y = if from.y() < to.y() { 1 } else { -1 };
+        let mut error = delta_x + delta_y;
+        let (mut x, mut y) = (from.x(), from.y());
+        loop {
+            self.plot(Point2d::from_x_y(x, y), color);
+            if x == to.x() && y == to.y() {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= delta_y {
+                error += delta_y;
+                x += step_x;
+            }
+            if doubled <= delta_x {
+                error += delta_x;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draws the pixels just inside the rectangle's edges.
+    pub fn rectangle(&mut self, rectangle: Rectangle<isize>, color: Color<u8>) {
+        if rectangle.width() <= 0 || rectangle.height() <= 0 {
+            return;
+        }
+        let (left, right) = (rectangle.left(), rectangle.right() - 1);
+        let (bottom, top) = (rectangle.bottom(), rectangle.top() - 1);
+        self.span(bottom, left, right + 1, color);
+        self.span(top, left, right + 1, color);
+        for y in (bottom + 1)..top {
+            self.plot(Point2d::from_x_y(left, y), color);
+            self.plot(Point2d::from_x_y(right, y), color);
+        }
+    }
+
+    pub fn fill_rectangle(&mut self, rectangle: Rectangle<isize>, color: Color<u8>) {
+        // Only visit the rows of the target, so a huge rectangle costs no more than it.
+        for y in rectangle.bottom().max(0)..rectangle.top().min(self.height()) {
+            self.span(y, rectangle.left(), rectangle.right(), color);
+        }
+    }
+
+    /// Draws a circle outline using the midpoint algorithm.
+    pub fn circle(&mut self, center: Point2d<isize>, radius: isize, color: Color<u8>) {
+        if radius < 0 {
+            return;
+        }
+        let (mut x, mut y) = (radius, 0);
+        let mut error = 1 - radius;
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x)] {
+                for (sx, sy) in [(dx, dy), (-dx, dy), (dx, -dy), (-dx, -dy)] {
+                    self.plot(center.shifted(sx, sy), color);
+                }
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Fills every pixel whose distance from the center is at most the radius.
+    pub fn fill_circle(&mut self, center: Point2d<isize>, radius: isize, color: Color<u8>) {
+        if radius < 0 {
+            return;
+        }
+        // Only visit the rows of the target, and square in a wider type so large radii
+        // can't overflow.
+        let bottom = center.y().saturating_sub(radius).max(0);
+        let top = center.y().saturating_add(radius).min(self.height() - 1);
+        let radius_squared = (radius as i128).pow(2);
+        for y in bottom..=top {
+            let dy = (y - center.y()) as i128;
+            let half_width = (radius_squared - dy * dy).isqrt();
+            let half_width = isize::try_from(half_width).unwrap_or(isize::MAX);
+            self.span(
+                y,
+                center.x().saturating_sub(half_width),
+                center.x().saturating_add(half_width).saturating_add(1),
+                color,
+            );
+        }
+    }
+
+    pub fn triangle(&mut self, vertices: [Point2d<isize>; 3], color: Color<u8>) {
+        self.polygon(&vertices, color);
+    }
+
+    pub fn fill_triangle(&mut self, vertices: [Point2d<isize>; 3], color: Color<u8>) {
+        self.fill_polygon(&vertices, color);
+    }
+
+    /// Draws the outline of a closed polygon, connecting the last vertex to the first.
+    pub fn polygon(&mut self, vertices: &[Point2d<isize>], color: Color<u8>) {
+        for (index, &from) in vertices.iter().enumerate() {
+            let to = vertices[(index + 1) % vertices.len()];
+            self.line(from, to, color);
+        }
+    }
+
+    /// Fills a closed polygon using the even-odd rule, so it may be concave or even
+    /// intersect itself.
+    pub fn fill_polygon(&mut self, vertices: &[Point2d<isize>], color: Color<u8>) {
+        if vertices.len() < 3 || self.is_outside_same_edge(vertices) {
+            return;
+        }
+        let bottom = vertices.iter().map(|p| p.y()).min().unwrap_or_default();
+        let top = vertices.iter().map(|p| p.y()).max().unwrap_or_default();
+        let bottom = bottom.max(0);
+        let top = top.min(self.height());
+        let mut crossings = Vec::with_capacity(vertices.len());
+        for y in bottom..top {
+            // Sample through the centers of the pixels in this row.
+            #[allow(clippy::cast_precision_loss)]
+            let sample_y = y as f32 + 0.5;
+            crossings.clear();
+            for (index, &from) in vertices.iter().enumerate() {
+                let to = vertices[(index + 1) % vertices.len()];
+                #[allow(clippy::cast_precision_loss)]
+                let (x0, y0, x1, y1) = (
+                    from.x() as f32,
+                    from.y() as f32,
+                    to.x() as f32,
+                    to.y() as f32,
+                );
+                if (y0 <= sample_y && sample_y < y1) || (y1 <= sample_y && sample_y < y0) {
+                    crossings.push(x0 + (sample_y - y0) * (x1 - x0) / (y1 - y0));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                // Cover the pixels whose centers lie between the two crossings.
+                #[allow(clippy::cast_possible_truncation)]
+                let left = (pair[0] - 0.5).ceil() as isize;
+                #[allow(clippy::cast_possible_truncation)]
+                let right = (pair[1] - 0.5).ceil() as isize;
+                self.span(y, left, right, color);
+            }
+        }
+    }
+
+    /// Draws a bitmap with its bottom-left corner at the given point, clipping whatever
+    /// falls outside the target.
+    pub fn bitmap(&mut self, bitmap: &Bitmap, bottom_left: Point2d<isize>) {
+        self.bitmap_region(bitmap, bitmap.bounds(), bottom_left);
+    }
+
+    /// Draws a frame of a sprite sheet with its bottom-left corner at the given point.
+    /// Nothing is drawn if there is no such frame.
+    pub fn sprite(&mut self, sheet: &SpriteSheet, frame: usize, bottom_left: Point2d<isize>) {
+        if let Some(region) = sheet.frame(frame) {
+            self.bitmap_region(sheet.bitmap(), region, bottom_left);
+        }
+    }
+
+    /// Draws part of a bitmap with the region's bottom-left corner at the given point.
+    pub fn bitmap_region(
+        &mut self,
+        bitmap: &Bitmap,
+        region: Rectangle<usize>,
+        bottom_left: Point2d<isize>,
+    ) {
+        let region = region.bound_to(&bitmap.bounds());
+        // The first column and row of the region that are visible, and where they go.
+        let skip_x = bottom_left.x().min(0).unsigned_abs();
+        let skip_y = bottom_left.y().min(0).unsigned_abs();
+        let start_x = usize::try_from(bottom_left.x().max(0)).unwrap_or(usize::MAX);
+        let start_y = usize::try_from(bottom_left.y().max(0)).unwrap_or(usize::MAX);
+        let columns = region
+            .width()
+            .saturating_sub(skip_x)
+            .min(self.target.width().saturating_sub(start_x));
+        if columns == 0 {
+            return;
+        }
+        let source_left = region.left() + skip_x;
+        let source_rows = (region.bottom().saturating_add(skip_y))..region.top();
+        for (source_y, target_y) in source_rows.zip(start_y..self.target.height()) {
+            let (Some(source), Some(destination)) =
+                (bitmap.row(source_y), self.target.row_mut(target_y))
+            else {
+                continue;
+            };
+            let source = &source[source_left..source_left + columns];
+            let destination = &mut destination[start_x..start_x + columns];
+            if self.blend_mode == BlendMode::Replace {
+                destination.copy_from_slice(source);
+            } else {
+                for (pixel, &color) in destination.iter_mut().zip(source) {
+                    *pixel = self.blend_mode.blend(color, *pixel);
+                }
+            }
+        }
+    }
+
+    /// Draws text with the top-left corner of its first character at the given point.
+    /// Each line is drawn below the last.
+    pub fn text(
+        &mut self,
+        font: &BitmapFont,
+        text: &str,
+        top_left: Point2d<isize>,
+        style: &TextStyle,
+    ) {
+        let scale = isize::try_from(style.scale()).unwrap_or(isize::MAX);
+        let glyph_width = isize::try_from(font.glyph_width()).unwrap_or(isize::MAX);
+        let advance = isize::try_from(font.advance())
+            .unwrap_or(isize::MAX)
+            .saturating_mul(scale);
+        let line_height = isize::try_from(font.line_height())
+            .unwrap_or(isize::MAX)
+            .saturating_mul(scale);
+        let mut line_top = top_left.y();
+        for line in font.lines(text, style) {
+            let mut left = top_left.x();
+            for character in line.chars() {
+                if let Some(glyph) = font.glyph(character) {
+                    for (row, pixels) in (0..).zip(glyph.chunks(font.glyph_width())) {
+                        for (column, _) in (0..).zip(pixels).filter(|(_, set)| **set) {
+                            // Saturate so huge scales clip rather than overflow.
+                            let bottom = line_top.saturating_sub(scale.saturating_mul(row + 1));
+                            let x = left.saturating_add(scale.saturating_mul(column));
+                            let pixel = Rectangle::new(
+                                bottom,
+                                x,
+                                bottom.saturating_add(scale) - bottom,
+                                x.saturating_add(scale) - x,
+                            );
+                            self.fill_rectangle(pixel, style.color());
+                        }
+                    }
+                }
+                left = left.saturating_add(advance);
+                if left >= self.width() || glyph_width == 0 {
+                    break;
+                }
+            }
+            line_top = line_top.saturating_sub(line_height);
+            if line_top <= 0 {
+                break;
+            }
+        }
+    }
+
+    /// Fills the pixels from `left` up to, but not including, `right` in row `y`.
+    fn span(&mut self, y: isize, left: isize, right: isize, color: Color<u8>) {
+        let Ok(y) = usize::try_from(y) else {
+            return;
+        };
+        let width = self.target.width();
+        let Some(row) = self.target.row_mut(y) else {
+            return;
+        };
+        let clamp = |x: isize| usize::try_from(x.max(0)).unwrap_or_default().min(width);
+        let (left, right) = (clamp(left), clamp(right));
+        if left < right {
+            blend_span(&mut row[left..right], color, self.blend_mode);
+        }
+    }
+
+    /// Clips a line to the target using the Cohen–Sutherland algorithm, returning the
+    /// ends of the visible part, or `None` if none of it is visible. The target is grown
+    /// by half a pixel on every side, so pixels the line passes through are kept even if
+    /// its exact path only touches their edge.
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    fn clip_line(
+        &self,
+        from: Point2d<isize>,
+        to: Point2d<isize>,
+    ) -> Option<(Point2d<isize>, Point2d<isize>)> {
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        const BOTTOM: u8 = 4;
+        const TOP: u8 = 8;
+
+        let (width, height) = (self.width(), self.height());
+        if width == 0 || height == 0 || self.is_outside_same_edge(&[from, to]) {
+            return None;
+        }
+        let (left, bottom) = (-0.5f64, -0.5f64);
+        let (right, top) = (width as f64 - 0.5, height as f64 - 0.5);
+        let outside = |(x, y): (f64, f64)| {
+            let mut edges = 0;
+            if x < left {
+                edges |= LEFT;
+            } else if x > right {
+                edges |= RIGHT;
+            }
+            if y < bottom {
+                edges |= BOTTOM;
+            } else if y > top {
+                edges |= TOP;
+            }
+            edges
+        };
+        let mut ends = [
+            (from.x() as f64, from.y() as f64),
+            (to.x() as f64, to.y() as f64),
+        ];
+        loop {
+            let edges = [outside(ends[0]), outside(ends[1])];
+            if edges[0] | edges[1] == 0 {
+                break;
+            }
+            if edges[0] & edges[1] != 0 {
+                return None;
+            }
+            // Move an end that is outside onto the edge it is beyond.
+            let index = usize::from(edges[0] == 0);
+            let [(x0, y0), (x1, y1)] = ends;
+            let edge = edges[index];
+            ends[index] = if edge & TOP != 0 {
+                (x0 + (x1 - x0) * (top - y0) / (y1 - y0), top)
+            } else if edge & BOTTOM != 0 {
+                (x0 + (x1 - x0) * (bottom - y0) / (y1 - y0), bottom)
+            } else if edge & RIGHT != 0 {
+                (right, y0 + (y1 - y0) * (right - x0) / (x1 - x0))
+            } else {
+                (left, y0 + (y1 - y0) * (left - x0) / (x1 - x0))
+            };
+        }
+        let point = |(x, y): (f64, f64)| {
+            Point2d::from_x_y(
+                (x.round() as isize).clamp(0, width - 1),
+                (y.round() as isize).clamp(0, height - 1),
+            )
+        };
+        Some((point(ends[0]), point(ends[1])))
+    }
+
+    /// Whether every point lies beyond the same edge of the target, so nothing between
+    /// them can be visible.
+    fn is_outside_same_edge(&self, points: &[Point2d<isize>]) -> bool {
+        let (width, height) = (self.width(), self.height());
+        points.iter().all(|p| p.x() < 0)
+            || points.iter().all(|p| p.y() < 0)
+            || points.iter().all(|p| p.x() >= width)
+            || points.iter().all(|p| p.y() >= height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmap::Bitmap;
+    use crate::bitmap_font::{BitmapFont, TextStyle};
+    use crate::color::{BlendMode, Color};
+    use crate::point_2d::Point2d;
+    use crate::rasterizer::Rasterizer;
+    use crate::rectangle::Rectangle;
+    use crate::render_target::{RenderTarget, RowOrder};
+
+    const SIZE: usize = 7;
+
+    fn point(x: isize, y: isize) -> Point2d<isize> {
+        Point2d::from_x_y(x, y)
+    }
+
+    /// Draws grey into a blank 7x7 target and returns its rows, top first, with '#'
+    /// for every grey pixel and '+' for any other color.
+    fn draw(drawing: impl FnOnce(&mut Rasterizer<'_, '_>, Color<u8>)) -> Vec<String> {
+        let grey = Color::from_rgb(0x80, 0x80, 0x80);
+        let mut pixels = vec![Color::default(); SIZE * SIZE];
+        let mut target =
+            RenderTarget::new(&mut pixels, SIZE, SIZE, SIZE, RowOrder::BottomUp).unwrap();
+        drawing(&mut Rasterizer::new(&mut target), grey);
+        (0..SIZE)
+            .rev()
+            .map(|y| {
+                target
+                    .row(y)
+                    .unwrap()
+                    .iter()
+                    .map(|&c| match c {
+                        c if c == Color::default() => '.',
+                        c if c == grey => '#',
+                        _ => '+',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_line() {
+        let rows = draw(|r, c| r.line(point(0, 0), point(6, 3), c));
+        assert_eq!(
+            vec![
+                ".......", ".......", ".......", ".....##", "...##..", ".##....", "#......",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_line_is_clipped() {
+        let rows = draw(|r, c| r.line(point(-3, 3), point(100, 3), c));
+        assert_eq!("#######", rows[3]);
+        let rows = draw(|r, c| r.line(point(-3, -1), point(100, -1), c));
+        assert!(rows.iter().all(|r| r == "......."));
+        // Far off-target ends are clipped rather than stepped over, and can't overflow.
+        let rows = draw(|r, c| r.line(point(isize::MIN, 3), point(isize::MAX, 3), c));
+        assert_eq!("#######", rows[3]);
+        let rows = draw(|r, c| {
+            let far = 1_000_000;
+            r.line(point(-far, -far), point(far, far), c);
+        });
+        assert_eq!(
+            vec![
+                "......#", ".....#.", "....#..", "...#...", "..#....", ".#.....", "#......",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_rectangles() {
+        let rows = draw(|r, c| r.rectangle(Rectangle::new(1, 1, 4, 5), c));
+        assert_eq!(
+            vec![
+                ".......", ".......", ".#####.", ".#...#.", ".#...#.", ".#####.", ".......",
+            ],
+            rows
+        );
+        let rows = draw(|r, c| r.fill_rectangle(Rectangle::new(-2, 5, 4, 9), c));
+        assert_eq!(
+            vec![
+                ".......", ".......", ".......", ".......", ".......", ".....##", ".....##",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_circles() {
+        let rows = draw(|r, c| r.circle(point(3, 3), 3, c));
+        assert_eq!(
+            vec![
+                "..###..", ".#...#.", "#.....#", "#.....#", "#.....#", ".#...#.", "..###..",
+            ],
+            rows
+        );
+        let rows = draw(|r, c| r.fill_circle(point(3, 3), 2, c));
+        assert_eq!(
+            vec![
+                ".......", "...#...", "..###..", ".#####.", "..###..", "...#...", ".......",
+            ],
+            rows
+        );
+        let rows = draw(|r, c| r.fill_circle(point(3, 3), isize::MAX, c));
+        assert!(rows.iter().all(|r| r == "#######"));
+    }
+
+    #[test]
+    fn test_triangles() {
+        let vertices = [point(0, 0), point(6, 0), point(0, 6)];
+        let rows = draw(|r, c| r.triangle(vertices, c));
+        assert_eq!(
+            vec![
+                "#......", "##.....", "#.#....", "#..#...", "#...#..", "#....#.", "#######",
+            ],
+            rows
+        );
+        let rows = draw(|r, c| r.fill_triangle(vertices, c));
+        assert_eq!(
+            vec![
+                ".......", ".......", "#......", "##.....", "###....", "####...", "#####..",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_fill_polygon_shares_edges() {
+        // Two squares sharing an edge cover each pixel exactly once, so adding them
+        // never brightens a pixel twice.
+        let left = [point(0, 0), point(3, 0), point(3, 4), point(0, 4)];
+        let right = [point(3, 0), point(7, 0), point(7, 4), point(3, 4)];
+        let rows = draw(|r, c| {
+            r.set_blend_mode(BlendMode::Additive);
+            r.fill_polygon(&left, c);
+            r.fill_polygon(&right, c);
+            r.fill_rectangle(Rectangle::new(3, 0, 1, 2), c);
+        });
+        assert_eq!(
+            vec![
+                ".......", ".......", ".......", "++#####", "#######", "#######", "#######",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_fill_concave_polygon() {
+        let vertices = [
+            point(0, 0),
+            point(7, 0),
+            point(7, 7),
+            point(4, 7),
+            point(4, 3),
+            point(3, 3),
+            point(3, 7),
+            point(0, 7),
+        ];
+        let rows = draw(|r, c| r.fill_polygon(&vertices, c));
+        assert_eq!(
+            vec![
+                "###.###", "###.###", "###.###", "###.###", "#######", "#######", "#######",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_blends_translucent_colors() {
+        let shadow = Color::from_rgba(0, 0, 0, 0x80);
+        let rows = draw(|r, c| {
+            r.fill_rectangle(Rectangle::new(0, 0, 7, 7), c);
+            r.fill_circle(point(3, 3), 1, shadow);
+        });
+        assert_eq!(
+            vec![
+                "#######", "#######", "###+###", "##+++##", "###+###", "#######", "#######",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_bitmap_is_clipped_and_blended() {
+        let grey = Color::from_rgb(0x80, 0x80, 0x80);
+        let clear = Color::from_rgba(0xFF, 0xFF, 0xFF, 0);
+        let white = Color::from_rgb(0xFF, 0xFF, 0xFF);
+        // A 3x3 grey square with a transparent middle, which shows the white beneath.
+        let mut pixels = vec![grey; 9];
+        pixels[4] = clear;
+        let bitmap = Bitmap::new(3, 3, pixels).unwrap();
+        let rows = draw(|r, _| {
+            r.bitmap(&bitmap, point(-1, 5));
+            r.bitmap(&Bitmap::new(1, 1, vec![white]).unwrap(), point(4, 3));
+            r.bitmap(&bitmap, point(3, 2));
+            r.bitmap(&bitmap, point(7, 0));
+            r.bitmap(&bitmap, point(-3, 0));
+        });
+        assert_eq!(
+            vec![
+                ".#.....", "##.....", "...###.", "...#+#.", "...###.", ".......", ".......",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let font = BitmapFont::embedded();
+        let style = TextStyle::new(Color::from_rgb(0x80, 0x80, 0x80));
+        // The second line starts 9 pixels below the first, off the bottom.
+        let rows = draw(|r, _| r.text(font, "Hi\nHi", point(-2, 8), &style));
+        assert_eq!(
+            vec![
+                "..#....", "..#..##", "###...#", "..#...#", "..#...#", "..#..##", ".......",
+            ],
+            rows
+        );
+        let rows = draw(|r, _| r.text(font, "-", point(-1, 7), &style.with_scale(2)));
+        assert_eq!(
+            vec![
+                ".......", ".......", ".......", ".......", ".......", ".......", "#######",
+            ],
+            rows
+        );
+        // The first pixel of the 'T' covers the whole target, and the rest are clipped.
+        let rows = draw(|r, _| r.text(font, "TT\nT", point(0, 7), &style.with_scale(usize::MAX)));
+        assert_eq!(vec!["#######"; 7], rows);
+    }
+}
@@ -1,10 +1,12 @@
-use crate::color::Color;
 use crate::game_state::GameState;
 use crate::input_state::InputState;
+use crate::platform_status::PlatformStatus;
+use crate::render_target::RenderTarget;
 
 #[derive(Debug)]
 pub struct RenderContext<'a> {
     pub input: &'a InputState,
     pub state: &'a mut GameState,
-    pub buffer: &'a mut [Color<u8>],
+    pub target: RenderTarget<'a>,
+    pub status: PlatformStatus,
 }
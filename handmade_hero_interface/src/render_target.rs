@@ -0,0 +1,264 @@
+use crate::application_error::{ApplicationError, Result};
+use crate::color::{BlendMode, Color};
+use crate::rectangle::Rectangle;
+
+/// The order rows of a render target are stored in memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RowOrder {
+    /// The first row in memory is the top of the image, as with a top-down Windows DIB.
+    TopDown,
+    /// The first row in memory is the bottom of the image.
+    BottomUp,
+}
+
+/// A mutable view of pixels to render into.
+///
+/// Coordinates are always measured in pixels from the bottom-left corner, with y
+/// increasing upward, the same as world coordinates. The row order only describes how
+/// rows are laid out in memory, so drawing code never needs to flip y itself. Rows are
+/// `pitch` pixels apart, which can be more than the width, e.g. for a sub-rectangle.
+#[derive(Debug)]
+pub struct RenderTarget<'a> {
+    pixels: &'a mut [Color<u8>],
+    width: usize,
+    height: usize,
+    pitch: usize,
+    row_order: RowOrder,
+}
+
+impl<'a> RenderTarget<'a> {
+    /// # Errors
+    /// An error is returned if the pitch is less than the width or the pixels cannot
+    /// hold every row.
+    pub fn new(
+        pixels: &'a mut [Color<u8>],
+        width: usize,
+        height: usize,
+        pitch: usize,
+        row_order: RowOrder,
+    ) -> Result<Self> {
+        if pitch < width {
+            return Err(ApplicationError::new(format!(
+                "The pitch ({pitch}) is less than the width ({width})"
+            )));
+        }
+        let required = Self::required_length(width, height, pitch);
+        if pixels.len() < required {
+            return Err(ApplicationError::new(format!(
+                "A {width}x{height} render target needs {required} pixels but only has {}",
+                pixels.len()
+            )));
+        }
+        Ok(Self {
+            pixels,
+            width,
+            height,
+            pitch,
+            row_order,
+        })
+    }
+
+    fn required_length(width: usize, height: usize, pitch: usize) -> usize {
+        if width == 0 || height == 0 {
+            0
+        } else {
+            (height - 1) * pitch + width
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn row_order(&self) -> RowOrder {
+        self.row_order
+    }
+
+    /// The bounds of the whole target, in the same coordinates used to draw.
+    #[inline]
+    #[must_use]
+    pub fn bounds(&self) -> Rectangle<usize> {
+        Rectangle::new(0, 0, self.height, self.width)
+    }
+
+    fn row_start(&self, y: usize) -> usize {
+        match self.row_order {
+            RowOrder::TopDown => (self.height - 1 - y) * self.pitch,
+            RowOrder::BottomUp => y * self.pitch,
+        }
+    }
+
+    /// The pixels of the row `y` pixels from the bottom.
+    #[must_use]
+    pub fn row(&self, y: usize) -> Option<&[Color<u8>]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = self.row_start(y);
+        Some(&self.pixels[start..start + self.width])
+    }
+
+    #[must_use]
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [Color<u8>]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = self.row_start(y);
+        Some(&mut self.pixels[start..start + self.width])
+    }
+
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color<u8>> {
+        self.row(y).and_then(|r| r.get(x)).copied()
+    }
+
+    #[must_use]
+    pub fn pixel_mut(&mut self, x: usize, y: usize) -> Option<&mut Color<u8>> {
+        self.row_mut(y).and_then(|r| r.get_mut(x))
+    }
+
+    /// Returns a view of part of this target, whose own coordinates start at the
+    /// rectangle's bottom-left corner. `None` is returned unless the rectangle lies
+    /// entirely within this target.
+    #[must_use]
+    pub fn view(&mut self, rectangle: Rectangle<usize>) -> Option<RenderTarget<'_>> {
+        if rectangle.right() > self.width || rectangle.top() > self.height {
+            return None;
+        }
+        let width = rectangle.width();
+        let height = rectangle.height();
+        let start = if width == 0 || height == 0 {
+            0
+        } else {
+            // The first row in memory is the view's top or bottom row, depending on order.
+            let first_row = match self.row_order {
+                RowOrder::TopDown => rectangle.top() - 1,
+                RowOrder::BottomUp => rectangle.bottom(),
+            };
+            self.row_start(first_row) + rectangle.left()
+        };
+        let length = Self::required_length(width, height, self.pitch);
+        Some(RenderTarget {
+            pixels: &mut self.pixels[start..start + length],
+            width,
+            height,
+            pitch: self.pitch,
+            row_order: self.row_order,
+        })
+    }
+
+    pub fn fill(&mut self, color: Color<u8>) {
+        for y in 0..self.height {
+            if let Some(row) = self.row_mut(y) {
+                row.fill(color);
+            }
+        }
+    }
+
+    /// Fills the part of the rectangle that falls within the target.
+    pub fn fill_rectangle(&mut self, rectangle: Rectangle<usize>, color: Color<u8>) {
+        self.blend_rectangle(rectangle, color, BlendMode::Replace);
+    }
+
+    /// Blends the color into the part of the rectangle that falls within the target.
+    pub fn blend_rectangle(
+        &mut self,
+        rectangle: Rectangle<usize>,
+        color: Color<u8>,
+        mode: BlendMode,
+    ) {
+        let rectangle = rectangle.bound_to(&self.bounds());
+        for y in rectangle.bottom()..rectangle.top() {
+            if let Some(row) = self.row_mut(y) {
+                blend_span(&mut row[rectangle.left()..rectangle.right()], color, mode);
+            }
+        }
+    }
+}
+
+pub(crate) fn blend_span(pixels: &mut [Color<u8>], color: Color<u8>, mode: BlendMode) {
+    if mode == BlendMode::Replace || (mode == BlendMode::Over && color.alpha() == u8::MAX) {
+        pixels.fill(color);
+    } else {
+        for pixel in pixels {
+            *pixel = mode.blend(color, *pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::rectangle::Rectangle;
+    use crate::render_target::{RenderTarget, RowOrder};
+
+    #[test]
+    fn test_rejects_small_buffers() {
+        let mut pixels = vec![Color::default(); 11];
+        assert!(RenderTarget::new(&mut pixels, 4, 3, 4, RowOrder::TopDown).is_err());
+        assert!(RenderTarget::new(&mut pixels, 4, 3, 3, RowOrder::TopDown).is_err());
+        assert!(RenderTarget::new(&mut pixels, 3, 3, 4, RowOrder::TopDown).is_ok());
+    }
+
+    #[test]
+    fn test_row_order() {
+        let red = Color::from_rgb(0xFF, 0, 0);
+        let mut pixels = vec![Color::default(); 6];
+        let mut target = RenderTarget::new(&mut pixels, 2, 3, 2, RowOrder::TopDown).unwrap();
+        *target.pixel_mut(1, 0).unwrap() = red;
+        assert!(target.pixel(2, 0).is_none());
+        assert_eq!(u32::from(red), u32::from(pixels[5]));
+
+        let mut pixels = vec![Color::default(); 6];
+        let mut target = RenderTarget::new(&mut pixels, 2, 3, 2, RowOrder::BottomUp).unwrap();
+        *target.pixel_mut(1, 0).unwrap() = red;
+        assert_eq!(u32::from(red), u32::from(pixels[1]));
+    }
+
+    #[test]
+    fn test_view_shares_pixels() {
+        let red = Color::from_rgb(0xFF, 0, 0);
+        for row_order in [RowOrder::TopDown, RowOrder::BottomUp] {
+            let mut pixels = vec![Color::default(); 20];
+            let mut target = RenderTarget::new(&mut pixels, 5, 4, 5, row_order).unwrap();
+            let mut view = target.view(Rectangle::new(1, 2, 2, 3)).unwrap();
+            assert_eq!((3, 2, 5), (view.width(), view.height(), view.pitch()));
+            view.fill(red);
+            assert!(target.view(Rectangle::new(3, 3, 2, 1)).is_none());
+
+            for y in 0..4 {
+                for x in 0..5 {
+                    let inside = (2..5).contains(&x) && (1..3).contains(&y);
+                    let color = u32::from(target.pixel(x, y).unwrap());
+                    assert_eq!(inside, color == u32::from(red), "({x}, {y})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_rectangle_clips() {
+        let red = Color::from_rgb(0xFF, 0, 0);
+        let mut pixels = vec![Color::default(); 9];
+        let mut target = RenderTarget::new(&mut pixels, 3, 3, 3, RowOrder::TopDown).unwrap();
+        target.fill_rectangle(Rectangle::new(2, 1, 5, 5), red);
+        let filled = pixels.iter().filter(|&&c| u32::from(c) == u32::from(red));
+        assert_eq!(2, filled.count());
+    }
+}
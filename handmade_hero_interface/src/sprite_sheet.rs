@@ -0,0 +1,120 @@
+use crate::application_error::{ApplicationError, Result};
+use crate::bitmap::Bitmap;
+use crate::rectangle::Rectangle;
+
+/// A bitmap holding many sprites, each identified by the index of its frame.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    bitmap: Bitmap,
+    frames: Vec<Rectangle<usize>>,
+}
+
+impl SpriteSheet {
+    /// Splits the bitmap into a grid of equally sized frames, numbered left to right
+    /// starting from the top row, the way sheets are usually laid out. Partial frames at
+    /// the right and bottom edges are ignored.
+    ///
+    /// # Errors
+    /// An error is returned if a frame is empty or larger than the bitmap.
+    pub fn from_grid(bitmap: Bitmap, frame_width: usize, frame_height: usize) -> Result<Self> {
+        if frame_width == 0 || frame_height == 0 {
+            return Err(ApplicationError::new("Sprite sheet frames cannot be empty"));
+        }
+        let columns = bitmap.width() / frame_width;
+        let rows = bitmap.height() / frame_height;
+        if columns == 0 || rows == 0 {
+            return Err(ApplicationError::new(format!(
+                "{frame_width}x{frame_height} frames do not fit in a {}x{} sprite sheet",
+                bitmap.width(),
+                bitmap.height()
+            )));
+        }
+        let frames = (0..rows)
+            .flat_map(|row| {
+                let bottom = bitmap.height() - (row + 1) * frame_height;
+                (0..columns).map(move |column| {
+                    Rectangle::new(bottom, column * frame_width, frame_height, frame_width)
+                })
+            })
+            .collect();
+        Ok(Self { bitmap, frames })
+    }
+
+    /// Uses the given regions of the bitmap as frames, in order.
+    ///
+    /// # Errors
+    /// An error is returned if a frame extends past the bitmap.
+    pub fn from_atlas(bitmap: Bitmap, frames: Vec<Rectangle<usize>>) -> Result<Self> {
+        if let Some(index) = frames
+            .iter()
+            .position(|f| f.right() > bitmap.width() || f.top() > bitmap.height())
+        {
+            return Err(ApplicationError::new(format!(
+                "Sprite sheet frame {index} extends past the {}x{} bitmap",
+                bitmap.width(),
+                bitmap.height()
+            )));
+        }
+        Ok(Self { bitmap, frames })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The region of the bitmap holding the frame.
+    #[inline]
+    #[must_use]
+    pub fn frame(&self, index: usize) -> Option<Rectangle<usize>> {
+        self.frames.get(index).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmap::Bitmap;
+    use crate::color::Color;
+    use crate::rectangle::Rectangle;
+    use crate::sprite_sheet::SpriteSheet;
+
+    fn bitmap(width: usize, height: usize) -> Bitmap {
+        Bitmap::new(width, height, vec![Color::default(); width * height]).unwrap()
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let sheet = SpriteSheet::from_grid(bitmap(7, 4), 3, 2).unwrap();
+        assert_eq!(4, sheet.frame_count());
+        // The first frame is at the top-left.
+        let first = sheet.frame(0).unwrap();
+        assert_eq!(
+            (2, 0, 4, 3),
+            (first.bottom(), first.left(), first.top(), first.right())
+        );
+        let last = sheet.frame(3).unwrap();
+        assert_eq!(
+            (0, 3, 2, 6),
+            (last.bottom(), last.left(), last.top(), last.right())
+        );
+        assert!(sheet.frame(4).is_none());
+        assert!(SpriteSheet::from_grid(bitmap(7, 4), 8, 2).is_err());
+        assert!(SpriteSheet::from_grid(bitmap(7, 4), 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_from_atlas() {
+        let frames = vec![Rectangle::new(0, 0, 4, 2), Rectangle::new(1, 2, 3, 5)];
+        let sheet = SpriteSheet::from_atlas(bitmap(7, 4), frames).unwrap();
+        assert_eq!(2, sheet.frame_count());
+        let frames = vec![Rectangle::new(1, 2, 4, 5)];
+        assert!(SpriteSheet::from_atlas(bitmap(7, 4), frames).is_err());
+    }
+}
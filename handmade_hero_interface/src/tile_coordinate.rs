@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// The position of a tile counted from the world's origin tile, which can be anywhere in
+/// a chunk or tile map, and the level it is on.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default, Serialize, Deserialize)]
+pub struct TileCoordinate {
+    pub x: i32,
+    pub y: i32,
+    /// The level, or floor, with higher levels above lower ones.
+    pub z: i32,
+}
+
+impl TileCoordinate {
+    #[inline]
+    #[must_use]
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn shifted(&self, delta_x: i32, delta_y: i32) -> Self {
+        Self::new(self.x + delta_x, self.y + delta_y, self.z)
+    }
+
+    /// The tile at the same position on another level.
+    #[inline]
+    #[must_use]
+    pub fn on_level(&self, z: i32) -> Self {
+        Self::new(self.x, self.y, z)
+    }
+}
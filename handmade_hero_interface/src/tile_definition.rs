@@ -0,0 +1,193 @@
+use crate::application_error::{ApplicationError, Result};
+use crate::color::Color;
+use serde::{Deserialize, Serialize};
+use std::ops::{BitOr, BitOrAssign};
+
+/// Special behavior a tile has, which can be combined with `|`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TileFlags(u8);
+
+impl TileFlags {
+    pub const NONE: Self = Self(0);
+    pub const DOOR: Self = Self(1);
+    /// Stairs or a ladder, leading to another level.
+    pub const STAIRS: Self = Self(1 << 1);
+    pub const WATER: Self = Self(1 << 2);
+    /// Harms whoever stands on it.
+    pub const HAZARD: Self = Self(1 << 3);
+
+    /// Indicates whether every flag in `other` is set.
+    #[inline]
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for TileFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for TileFlags {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Describes one kind of tile: whether it can be walked on, how it is drawn and what it
+/// does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "TileDefinitionData")]
+pub struct TileDefinition {
+    name: String,
+    traversable: bool,
+    color: Color<u8>,
+    movement_cost: f32,
+    flags: TileFlags,
+    level_change: i32,
+}
+
+impl TileDefinition {
+    /// A plain tile that can be walked on at full speed.
+    #[must_use]
+    pub fn new(name: impl Into<String>, color: Color<u8>) -> Self {
+        Self {
+            name: name.into(),
+            traversable: true,
+            color,
+            movement_cost: 1f32,
+            flags: TileFlags::NONE,
+            level_change: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_traversable(mut self, traversable: bool) -> Self {
+        self.traversable = traversable;
+        self
+    }
+
+    /// How many times longer it takes to cross the tile than a floor tile.
+    ///
+    /// # Panics
+    /// Panics if the cost is not a finite number greater than zero.
+    #[inline]
+    #[must_use]
+    pub fn with_movement_cost(mut self, movement_cost: f32) -> Self {
+        assert!(
+            Self::is_valid_movement_cost(movement_cost),
+            "The movement cost must be finite and greater than zero, not {movement_cost}"
+        );
+        self.movement_cost = movement_cost;
+        self
+    }
+
+    /// Whether the cost can be passed to `with_movement_cost`.
+    #[inline]
+    #[must_use]
+    pub fn is_valid_movement_cost(movement_cost: f32) -> bool {
+        movement_cost.is_finite() && movement_cost > 0f32
+    }
+
+    /// Adds the flags to those the tile already has.
+    #[inline]
+    #[must_use]
+    pub fn with_flags(mut self, flags: TileFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Makes the tile stairs leading up this many levels, or down if negative.
+    #[inline]
+    #[must_use]
+    pub fn with_stairs(mut self, level_change: i32) -> Self {
+        self.flags |= TileFlags::STAIRS;
+        self.level_change = level_change;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_traversable(&self) -> bool {
+        self.traversable
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn color(&self) -> Color<u8> {
+        self.color
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn movement_cost(&self) -> f32 {
+        self.movement_cost
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn flags(&self) -> TileFlags {
+        self.flags
+    }
+
+    /// How many levels the tile leads up, or down if negative, if it is stairs.
+    #[inline]
+    #[must_use]
+    pub fn level_change(&self) -> Option<i32> {
+        self.flags
+            .contains(TileFlags::STAIRS)
+            .then_some(self.level_change)
+    }
+}
+
+/// A `TileDefinition` as it is serialized, before its movement cost has been checked, so
+/// a saved or recorded game cannot bring back a cost the builder would refuse.
+#[derive(Deserialize)]
+struct TileDefinitionData {
+    name: String,
+    traversable: bool,
+    color: Color<u8>,
+    movement_cost: f32,
+    flags: TileFlags,
+    level_change: i32,
+}
+
+impl TryFrom<TileDefinitionData> for TileDefinition {
+    type Error = ApplicationError;
+
+    fn try_from(data: TileDefinitionData) -> Result<Self> {
+        if !Self::is_valid_movement_cost(data.movement_cost) {
+            return Err(ApplicationError::new(format!(
+                "The tile {:?} has a movement cost of {}, which is not a positive number",
+                data.name, data.movement_cost
+            )));
+        }
+        Ok(Self {
+            name: data.name,
+            traversable: data.traversable,
+            color: data.color,
+            movement_cost: data.movement_cost,
+            flags: data.flags,
+            level_change: data.level_change,
+        })
+    }
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 pub struct TileMapKey {
     pub x: isize,
     pub y: isize,
+    /// The level, or floor, with higher levels above lower ones.
+    pub z: i32,
 }
 
 impl TileMapKey {
@@ -18,4 +20,10 @@ impl TileMapKey {
     pub fn y(&self) -> isize {
         self.y
     }
+
+    #[inline]
+    #[must_use]
+    pub fn z(&self) -> i32 {
+        self.z
+    }
 }
@@ -0,0 +1,140 @@
+//! Tiles are stored as ids. The registry maps each id to its definition, which collision
+//! and rendering consult, so adding a kind of tile only means registering it.
+
+use crate::color::Color;
+use crate::tile_definition::{TileDefinition, TileFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileRegistry {
+    definitions: HashMap<u32, TileDefinition>,
+}
+
+impl TileRegistry {
+    pub const FLOOR: u32 = 0;
+    pub const WALL: u32 = 1;
+    pub const STAIRS_UP: u32 = 2;
+    pub const STAIRS_DOWN: u32 = 3;
+    pub const WATER: u32 = 4;
+
+    /// A registry with no tiles in it.
+    #[inline]
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            definitions: HashMap::new(),
+        }
+    }
+
+    /// A registry with the built-in floor, wall, stairs and water tiles.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            Self::FLOOR,
+            TileDefinition::new("floor", Color::from_rgb(0xCC, 0xCC, 0xCC)),
+        );
+        registry.register(
+            Self::WALL,
+            TileDefinition::new("wall", Color::from_rgb(0xFF, 0xFF, 0xFF)).with_traversable(false),
+        );
+        registry.register(
+            Self::STAIRS_UP,
+            TileDefinition::new("stairs up", Color::from_rgb(0xA0, 0x70, 0x40)).with_stairs(1),
+        );
+        registry.register(
+            Self::STAIRS_DOWN,
+            TileDefinition::new("stairs down", Color::from_rgb(0x60, 0x40, 0x20)).with_stairs(-1),
+        );
+        registry.register(
+            Self::WATER,
+            TileDefinition::new("water", Color::from_rgb(0x40, 0x60, 0xC0))
+                .with_movement_cost(2f32)
+                .with_flags(TileFlags::WATER),
+        );
+        registry
+    }
+
+    /// Defines the tile id, returning the definition it replaces, if any.
+    pub fn register(&mut self, id: u32, definition: TileDefinition) -> Option<TileDefinition> {
+        self.definitions.insert(id, definition)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn get(&self, id: u32) -> Option<&TileDefinition> {
+        self.definitions.get(&id)
+    }
+
+    /// Finds the id of the tile with the name.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.definitions
+            .iter()
+            .find(|(_, d)| d.name() == name)
+            .map(|(id, _)| *id)
+    }
+}
+
+impl Default for TileRegistry {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::tile_definition::{TileDefinition, TileFlags};
+    use crate::tile_registry::TileRegistry;
+
+    #[test]
+    fn test_built_in_tiles() {
+        let registry = TileRegistry::new();
+        let floor = registry.get(TileRegistry::FLOOR).unwrap();
+        assert!(floor.is_traversable());
+        assert_eq!(None, floor.level_change());
+        assert!(!registry.get(TileRegistry::WALL).unwrap().is_traversable());
+        let stairs_down = registry.get(TileRegistry::STAIRS_DOWN).unwrap();
+        assert_eq!(Some(-1), stairs_down.level_change());
+        assert!(stairs_down.flags().contains(TileFlags::STAIRS));
+        assert_eq!(Some(TileRegistry::WATER), registry.find("water"));
+        assert!(registry.get(99).is_none());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = TileRegistry::empty();
+        let lava = TileDefinition::new("lava", Color::from_rgb(0xFF, 0x40, 0x00))
+            .with_movement_cost(3f32)
+            .with_flags(TileFlags::HAZARD | TileFlags::WATER);
+        assert!(registry.register(10, lava.clone()).is_none());
+        assert_eq!(Some(&lava), registry.get(10));
+
+        let flags = registry.get(10).unwrap().flags();
+        assert!(flags.contains(TileFlags::HAZARD));
+        assert!(!flags.contains(TileFlags::DOOR));
+        assert!(!flags.contains(TileFlags::DOOR | TileFlags::HAZARD));
+        assert!(TileFlags::NONE.is_empty());
+
+        let door = TileDefinition::new("door", Color::default()).with_flags(TileFlags::DOOR);
+        assert_eq!(Some(lava), registry.register(10, door));
+    }
+
+    #[test]
+    fn test_movement_cost_must_be_positive() {
+        assert!(TileDefinition::is_valid_movement_cost(0.5f32));
+        for cost in [0f32, -1f32, f32::NAN, f32::INFINITY] {
+            assert!(!TileDefinition::is_valid_movement_cost(cost));
+        }
+
+        // Deserializing checks the cost too.
+        let water = TileDefinition::new("water", Color::default()).with_movement_cost(2f32);
+        let mut value = serde_json::to_value(&water).unwrap();
+        assert_eq!(water, serde_json::from_value(value.clone()).unwrap());
+        value["movement_cost"] = serde_json::json!(0f32);
+        assert!(serde_json::from_value::<TileDefinition>(value).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Tiles are kept in fixed-size chunks, which are allocated the first time one of their
+//! tiles is set. Each level has its own chunks. Levels can be any size and shape, and
+//! only the chunks they touch take up memory.
+
+use crate::application_error::{ApplicationError, Result};
+use crate::tile_coordinate::TileCoordinate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+struct ChunkKey {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "TileStoreData")]
+pub struct TileStore {
+    chunk_width: u16,
+    chunk_height: u16,
+    /// The tiles of each chunk, bottom row first. Tiles that were never set are `None`.
+    chunks: HashMap<ChunkKey, Vec<Option<u32>>>,
+}
+
+impl TileStore {
+    pub const DEFAULT_CHUNK_SIZE: u16 = 16;
+
+    /// # Errors
+    /// An error is returned if either dimension of the chunks is zero.
+    pub fn new(chunk_width: u16, chunk_height: u16) -> Result<Self> {
+        if chunk_width == 0 || chunk_height == 0 {
+            return Err(ApplicationError::new(format!(
+                "Tile chunks cannot be {chunk_width}x{chunk_height}"
+            )));
+        }
+        Ok(Self {
+            chunk_width,
+            chunk_height,
+            chunks: HashMap::new(),
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chunk_width(&self) -> u16 {
+        self.chunk_width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chunk_height(&self) -> u16 {
+        self.chunk_height
+    }
+
+    /// The number of chunks that have been allocated.
+    #[inline]
+    #[must_use]
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The tile at the coordinate, or `None` if it was never set.
+    #[must_use]
+    pub fn get(&self, coordinate: TileCoordinate) -> Option<u32> {
+        let (key, index) = self.locate(coordinate);
+        self.chunks.get(&key).and_then(|c| c[index])
+    }
+
+    /// Sets the tile at the coordinate, allocating its chunk if need be.
+    pub fn set(&mut self, coordinate: TileCoordinate, tile: u32) {
+        let (key, index) = self.locate(coordinate);
+        let size = usize::from(self.chunk_width) * usize::from(self.chunk_height);
+        let chunk = self.chunks.entry(key).or_insert_with(|| vec![None; size]);
+        chunk[index] = Some(tile);
+    }
+
+    /// The bottom-left corner and the corner just above and to the right of the top-right
+    /// corner of the smallest rectangle holding every tile that has been set on the level,
+    /// or `None` if no tiles have been set on it.
+    #[must_use]
+    pub fn bounds(&self, level: i32) -> Option<(TileCoordinate, TileCoordinate)> {
+        let width = i32::from(self.chunk_width);
+        let height = i32::from(self.chunk_height);
+        self.chunks
+            .iter()
+            .filter(|(key, _)| key.z == level)
+            .flat_map(|(key, tiles)| {
+                let origin = TileCoordinate::new(key.x * width, key.y * height, level);
+                let positions = (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)));
+                positions
+                    .zip(tiles)
+                    .filter_map(move |((x, y), tile)| tile.map(|_| origin.shifted(x, y)))
+            })
+            .fold(None, |bounds, tile| {
+                let above_right =
+                    TileCoordinate::new(tile.x.saturating_add(1), tile.y.saturating_add(1), level);
+                Some(match bounds {
+                    None => (tile, above_right),
+                    Some((bottom_left, top_right)) => (
+                        TileCoordinate::new(
+                            i32::min(bottom_left.x, tile.x),
+                            i32::min(bottom_left.y, tile.y),
+                            level,
+                        ),
+                        TileCoordinate::new(
+                            i32::max(top_right.x, above_right.x),
+                            i32::max(top_right.y, above_right.y),
+                            level,
+                        ),
+                    ),
+                })
+            })
+    }
+
+    /// The chunk holding the coordinate and the index of the tile within it.
+    #[allow(clippy::cast_sign_loss)]
+    fn locate(&self, coordinate: TileCoordinate) -> (ChunkKey, usize) {
+        let width = i32::from(self.chunk_width);
+        let height = i32::from(self.chunk_height);
+        let key = ChunkKey {
+            x: coordinate.x.div_euclid(width),
+            y: coordinate.y.div_euclid(height),
+            z: coordinate.z,
+        };
+        // The remainders are never negative.
+        let column = coordinate.x.rem_euclid(width) as usize;
+        let row = coordinate.y.rem_euclid(height) as usize;
+        (key, row * usize::from(self.chunk_width) + column)
+    }
+}
+
+/// A `TileStore` as it is serialized, before it has been checked. A recording could hold
+/// anything, and a bad chunk size or chunk would otherwise panic when a tile is looked up.
+#[derive(Deserialize)]
+struct TileStoreData {
+    chunk_width: u16,
+    chunk_height: u16,
+    chunks: HashMap<ChunkKey, Vec<Option<u32>>>,
+}
+
+impl TryFrom<TileStoreData> for TileStore {
+    type Error = ApplicationError;
+
+    fn try_from(data: TileStoreData) -> Result<Self> {
+        let mut store = Self::new(data.chunk_width, data.chunk_height)?;
+        let width = i32::from(data.chunk_width);
+        let height = i32::from(data.chunk_height);
+        let size = usize::from(data.chunk_width) * usize::from(data.chunk_height);
+        let fits = |index: i32, size: i32| {
+            index
+                .checked_mul(size)
+                .and_then(|origin| origin.checked_add(size - 1))
+                .is_some()
+        };
+        for (key, tiles) in &data.chunks {
+            if tiles.len() != size || !fits(key.x, width) || !fits(key.y, height) {
+                return Err(ApplicationError::new(format!(
+                    "The tile chunk at ({}, {}, {}) does not fit {width}x{height} chunks",
+                    key.x, key.y, key.z
+                )));
+            }
+        }
+        store.chunks = data.chunks;
+        Ok(store)
+    }
+}
+
+impl Default for TileStore {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            chunk_width: Self::DEFAULT_CHUNK_SIZE,
+            chunk_height: Self::DEFAULT_CHUNK_SIZE,
+            chunks: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tile_coordinate::TileCoordinate;
+    use crate::tile_store::{ChunkKey, TileStore};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_chunks_are_allocated_lazily() {
+        let mut store = TileStore::new(4, 3).unwrap();
+        assert_eq!(None, store.get(TileCoordinate::new(0, 0, 0)));
+        assert_eq!(0, store.chunk_count());
+
+        store.set(TileCoordinate::new(0, 0, 0), 1);
+        store.set(TileCoordinate::new(3, 2, 0), 2);
+        assert_eq!(1, store.chunk_count());
+        // Negative coordinates belong to the chunks below and to the left.
+        store.set(TileCoordinate::new(-1, -1, 0), 3);
+        store.set(TileCoordinate::new(-4, -3, 0), 4);
+        assert_eq!(2, store.chunk_count());
+        store.set(TileCoordinate::new(i32::MAX, i32::MIN, 0), 5);
+        assert_eq!(3, store.chunk_count());
+
+        assert_eq!(Some(1), store.get(TileCoordinate::new(0, 0, 0)));
+        assert_eq!(Some(2), store.get(TileCoordinate::new(3, 2, 0)));
+        assert_eq!(Some(3), store.get(TileCoordinate::new(-1, -1, 0)));
+        assert_eq!(Some(4), store.get(TileCoordinate::new(-4, -3, 0)));
+        assert_eq!(
+            Some(5),
+            store.get(TileCoordinate::new(i32::MAX, i32::MIN, 0))
+        );
+        // Allocated, but never set.
+        assert_eq!(None, store.get(TileCoordinate::new(1, 0, 0)));
+        assert_eq!(None, store.get(TileCoordinate::new(4, 0, 0)));
+        // Each level has its own chunks.
+        assert_eq!(None, store.get(TileCoordinate::new(0, 0, 1)));
+        store.set(TileCoordinate::new(0, 0, -1), 6);
+        assert_eq!(4, store.chunk_count());
+        assert_eq!(Some(6), store.get(TileCoordinate::new(0, 0, -1)));
+        assert_eq!(Some(1), store.get(TileCoordinate::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut store = TileStore::new(4, 3).unwrap();
+        assert_eq!(None, store.bounds(0));
+        store.set(TileCoordinate::new(2, 1, 0), 0);
+        assert_eq!(
+            Some((TileCoordinate::new(2, 1, 0), TileCoordinate::new(3, 2, 0))),
+            store.bounds(0)
+        );
+        store.set(TileCoordinate::new(-5, 7, 0), 0);
+        assert_eq!(
+            Some((TileCoordinate::new(-5, 1, 0), TileCoordinate::new(3, 8, 0))),
+            store.bounds(0)
+        );
+        assert_eq!(None, store.bounds(1));
+    }
+
+    #[test]
+    fn test_empty_chunks_are_rejected() {
+        assert!(TileStore::new(0, 3).is_err());
+        assert!(TileStore::new(3, 0).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_stores_are_rejected() {
+        let config = bincode::config::standard();
+        let decode = |width: u16, height: u16, key: ChunkKey, tiles: Vec<Option<u32>>| {
+            let chunks = HashMap::from([(key, tiles)]);
+            let bytes = bincode::serde::encode_to_vec((width, height, chunks), config).unwrap();
+            bincode::serde::decode_from_slice::<TileStore, _>(&bytes, config).map(|(s, _)| s)
+        };
+        let origin = ChunkKey { x: 0, y: 0, z: 0 };
+        let store = decode(2, 2, origin, vec![Some(1), None, None, None]).unwrap();
+        assert_eq!(Some(1), store.get(TileCoordinate::new(0, 0, 0)));
+
+        assert!(decode(0, 2, origin, Vec::new()).is_err());
+        assert!(decode(2, 2, origin, vec![Some(1)]).is_err());
+        let far = ChunkKey {
+            x: i32::MAX,
+            y: 0,
+            z: 0,
+        };
+        assert!(decode(2, 2, far, vec![None; 4]).is_err());
+    }
+}
@@ -0,0 +1,743 @@
+//! Imports maps made with the Tiled editor, saved in its JSON format.
+//!
+//! Tile layers become tiles in the world, with the map's bottom-left tile at the tile
+//! given by the map's `origin_x` and `origin_y` properties, or the origin tile if it has
+//! none. Each layer is placed on the level given by its `level` property, or its group's,
+//! or level 0. Tiled counts rows from the top down, so they are flipped to count from the
+//! bottom up like the rest of the world.
+//!
+//! A tile's id in the world is its global id in the map less one, so the first tile of
+//! the first tileset is tile 0. Tiles in a tileset with a type or custom properties are
+//! registered as tile types, replacing any with the same id. The properties understood
+//! are `name` (string), `color` (color), `traversable` (bool), `movement_cost` (float),
+//! `stairs` (int, the levels they lead up) and the `door`, `water` and `hazard` flags
+//! (bool). Tiles with neither keep whatever definition the world has.
+//!
+//! Objects in object layers are kept as they are for the game to interpret, such as
+//! where the player starts. Maps must be finite, with embedded tilesets and tile layers
+//! saved as CSV.
+
+use crate::application_error::{ApplicationError, ErrorKind, Result};
+use crate::color::Color;
+use crate::tile_coordinate::TileCoordinate;
+use crate::tile_definition::{TileDefinition, TileFlags};
+use crate::world::World;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tiled sets the highest bits of a global tile id when the tile is flipped or rotated.
+const GLOBAL_ID_MASK: u32 = 0x0FFF_FFFF;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledMap {
+    origin: TileCoordinate,
+    layers: Vec<TiledLayer>,
+    tile_types: Vec<(u32, TileDefinition)>,
+    objects: Vec<TiledObject>,
+}
+
+impl TiledMap {
+    /// # Errors
+    /// An error is returned if the file cannot be read. An `ErrorKind::MalformedLevel`
+    /// error is returned if its contents are malformed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            ApplicationError::wrap(
+                format!("Could not read the Tiled map {}", path.display()),
+                e,
+            )
+        })?;
+        Self::parse(&source)
+    }
+
+    /// # Errors
+    /// An `ErrorKind::MalformedLevel` error is returned if the source is not a Tiled map,
+    /// a layer does not have as many tiles as its size says, a property has the wrong
+    /// type or the map uses a feature that is not supported.
+    pub fn parse(source: &str) -> Result<Self> {
+        let map: RawMap = serde_json::from_str(source).map_err(|e| {
+            ApplicationError::wrap("The Tiled map is malformed", e)
+                .with_kind(ErrorKind::MalformedLevel)
+        })?;
+        if map.infinite {
+            return Err(malformed("Infinite Tiled maps are not supported"));
+        }
+        let properties = Properties::new(&map.properties)?;
+        let origin = TileCoordinate::new(
+            properties.int("origin_x")?.unwrap_or(0),
+            properties.int("origin_y")?.unwrap_or(0),
+            0,
+        );
+        let mut importer = Importer {
+            map: &map,
+            layers: Vec::new(),
+            objects: Vec::new(),
+        };
+        importer.import_layers(&map.layers, 0)?;
+        let mut tile_types = Vec::new();
+        for tileset in &map.tilesets {
+            if let Some(source) = &tileset.source {
+                return Err(malformed(format!(
+                    "The tileset {source} is external; embed it in the map"
+                )));
+            }
+            for tile in &tileset.tiles {
+                let id = tileset
+                    .first_global_id
+                    .checked_add(tile.id)
+                    .and_then(|global_id| global_id.checked_sub(1))
+                    .ok_or_else(|| {
+                        malformed(format!("Tile {} has an invalid global id", tile.id))
+                    })?;
+                if let Some(definition) = Self::tile_definition(id, tile)? {
+                    tile_types.push((id, definition));
+                }
+            }
+        }
+        Ok(Self {
+            origin,
+            layers: importer.layers,
+            tile_types,
+            objects: importer.objects,
+        })
+    }
+
+    fn tile_definition(id: u32, tile: &RawTile) -> Result<Option<TileDefinition>> {
+        if tile.kind.is_empty() && tile.properties.is_empty() {
+            return Ok(None);
+        }
+        let properties = Properties::new(&tile.properties)?;
+        let name = match properties.string("name")? {
+            Some(name) => name.to_string(),
+            None if !tile.kind.is_empty() => tile.kind.clone(),
+            None => format!("tile {id}"),
+        };
+        let color = match properties.string("color")? {
+            Some(color) => parse_color(color)?,
+            None => Color::default(),
+        };
+        let mut definition = TileDefinition::new(name, color);
+        if let Some(traversable) = properties.bool("traversable")? {
+            definition = definition.with_traversable(traversable);
+        }
+        if let Some(movement_cost) = properties.float("movement_cost")? {
+            #[allow(clippy::cast_possible_truncation)]
+            let movement_cost = movement_cost as f32;
+            if !TileDefinition::is_valid_movement_cost(movement_cost) {
+                return Err(malformed(format!(
+                    "Tile {id} has a movement cost of {movement_cost}, which is not a positive number"
+                )));
+            }
+            definition = definition.with_movement_cost(movement_cost);
+        }
+        if let Some(level_change) = properties.int("stairs")? {
+            definition = definition.with_stairs(level_change);
+        }
+        for (flag, name) in [
+            (TileFlags::DOOR, "door"),
+            (TileFlags::WATER, "water"),
+            (TileFlags::HAZARD, "hazard"),
+        ] {
+            if properties.bool(name)? == Some(true) {
+                definition = definition.with_flags(flag);
+            }
+        }
+        Ok(Some(definition))
+    }
+
+    /// The tile the map's bottom-left tile is placed at on each level.
+    #[inline]
+    #[must_use]
+    pub fn origin(&self) -> TileCoordinate {
+        self.origin
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn layers(&self) -> &[TiledLayer] {
+        &self.layers
+    }
+
+    /// The tile types defined by the map's tilesets, with their ids.
+    #[inline]
+    #[must_use]
+    pub fn tile_types(&self) -> &[(u32, TileDefinition)] {
+        &self.tile_types
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn objects(&self) -> &[TiledObject] {
+        &self.objects
+    }
+
+    /// Finds the first object with the name.
+    #[must_use]
+    pub fn find_object(&self, name: &str) -> Option<&TiledObject> {
+        self.objects.iter().find(|o| o.name == name)
+    }
+
+    /// Registers the map's tile types and sets the tiles of every layer in the world.
+    /// Empty tiles are skipped, so a layer only replaces the tiles it has.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn populate(&self, world: &mut World) {
+        for (id, definition) in &self.tile_types {
+            world.tile_types.register(*id, definition.clone());
+        }
+        for layer in &self.layers {
+            let origin = self.origin.on_level(layer.level);
+            for row in 0..layer.rows {
+                for column in 0..layer.columns {
+                    if let Some(tile) = layer.tile(column, row) {
+                        world.set_tile(origin.shifted(column as i32, row as i32), tile);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One tile layer of a Tiled map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledLayer {
+    name: String,
+    level: i32,
+    columns: usize,
+    rows: usize,
+    /// Tile ids, bottom row first. Empty tiles are `None`.
+    tiles: Vec<Option<u32>>,
+}
+
+impl TiledLayer {
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The id of the tile, with rows counted from the bottom, or `None` if it is empty.
+    ///
+    /// # Panics
+    /// Panics if the column or row is outside the layer.
+    #[inline]
+    #[must_use]
+    pub fn tile(&self, column: usize, row: usize) -> Option<u32> {
+        assert!(column < self.columns, "The column is outside the layer");
+        self.tiles[row * self.columns + column]
+    }
+}
+
+/// The value of a custom property. Colors, files and objects are given as Tiled saves
+/// them, as strings and ids.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// An object from an object layer, measured in tiles from the bottom-left corner of the
+/// map's origin tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledObject {
+    name: String,
+    kind: String,
+    level: i32,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    properties: HashMap<String, TiledProperty>,
+}
+
+impl TiledObject {
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The object's type, or class as newer versions of Tiled call it.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// The left edge.
+    #[inline]
+    #[must_use]
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    /// The bottom edge.
+    #[inline]
+    #[must_use]
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// The tile holding the object's bottom-left corner.
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn tile(&self) -> TileCoordinate {
+        TileCoordinate::new(self.x.floor() as i32, self.y.floor() as i32, self.level)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn property(&self, name: &str) -> Option<&TiledProperty> {
+        self.properties.get(name)
+    }
+}
+
+/// Walks the layers of a map, flattening groups.
+struct Importer<'a> {
+    map: &'a RawMap,
+    layers: Vec<TiledLayer>,
+    objects: Vec<TiledObject>,
+}
+
+impl Importer<'_> {
+    fn import_layers(&mut self, layers: &[RawLayer], level: i32) -> Result<()> {
+        for layer in layers {
+            match layer {
+                RawLayer::Tiles {
+                    name,
+                    width,
+                    height,
+                    data,
+                    properties,
+                } => {
+                    let level = Properties::new(properties)?.int("level")?.unwrap_or(level);
+                    self.import_tiles(name, level, *width, *height, data)?;
+                }
+                RawLayer::Objects {
+                    objects,
+                    properties,
+                    ..
+                } => {
+                    let level = Properties::new(properties)?.int("level")?.unwrap_or(level);
+                    for object in objects {
+                        self.import_object(object, level)?;
+                    }
+                }
+                RawLayer::Group { layers, properties } => {
+                    let level = Properties::new(properties)?.int("level")?.unwrap_or(level);
+                    self.import_layers(layers, level)?;
+                }
+                RawLayer::Other => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn import_tiles(
+        &mut self,
+        name: &str,
+        level: i32,
+        columns: usize,
+        rows: usize,
+        data: &RawData,
+    ) -> Result<()> {
+        let RawData::Array(data) = data else {
+            return Err(malformed(format!(
+                "The layer {name} is compressed or encoded; save tile layers as CSV"
+            )));
+        };
+        if columns.checked_mul(rows) != Some(data.len()) {
+            return Err(malformed(format!(
+                "The {columns}x{rows} layer {name} cannot hold {} tiles",
+                data.len()
+            )));
+        }
+        // Tiled lists the top row first, but rows are counted from the bottom up.
+        let tiles = data
+            .chunks(columns.max(1))
+            .rev()
+            .flatten()
+            .map(|global_id| (global_id & GLOBAL_ID_MASK).checked_sub(1))
+            .collect();
+        self.layers.push(TiledLayer {
+            name: name.to_string(),
+            level,
+            columns,
+            rows,
+            tiles,
+        });
+        Ok(())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn import_object(&mut self, object: &RawObject, level: i32) -> Result<()> {
+        let map_height = self.map.height as f32 * self.map.tile_height;
+        // Tile objects are anchored at their bottom edge, everything else at its top.
+        let bottom = if object.global_id.is_some() {
+            object.y
+        } else {
+            object.y + object.height
+        };
+        let properties = object
+            .properties
+            .iter()
+            .map(|p| Ok((p.name.clone(), p.value()?)))
+            .collect::<Result<_>>()?;
+        self.objects.push(TiledObject {
+            name: object.name.clone(),
+            kind: object.kind.clone(),
+            level: Properties::new(&object.properties)?
+                .int("level")?
+                .unwrap_or(level),
+            x: object.x / self.map.tile_width,
+            y: (map_height - bottom) / self.map.tile_height,
+            width: object.width / self.map.tile_width,
+            height: object.height / self.map.tile_height,
+            properties,
+        });
+        Ok(())
+    }
+}
+
+/// Looks up custom properties by name, checking their types.
+struct Properties<'a> {
+    properties: HashMap<&'a str, &'a RawProperty>,
+}
+
+impl<'a> Properties<'a> {
+    fn new(properties: &'a [RawProperty]) -> Result<Self> {
+        // Check every value, even those that are not looked up.
+        for property in properties {
+            property.value()?;
+        }
+        Ok(Self {
+            properties: properties.iter().map(|p| (p.name.as_str(), p)).collect(),
+        })
+    }
+
+    fn int(&self, name: &str) -> Result<Option<i32>> {
+        let Some(property) = self.properties.get(name) else {
+            return Ok(None);
+        };
+        match property.value()? {
+            TiledProperty::Int(value) => i32::try_from(value)
+                .map(Some)
+                .map_err(|_| malformed(format!("The property {name} is out of range"))),
+            _ => Err(wrong_type(name, "an int")),
+        }
+    }
+
+    fn float(&self, name: &str) -> Result<Option<f64>> {
+        let Some(property) = self.properties.get(name) else {
+            return Ok(None);
+        };
+        match property.value()? {
+            TiledProperty::Float(value) => Ok(Some(value)),
+            #[allow(clippy::cast_precision_loss)]
+            TiledProperty::Int(value) => Ok(Some(value as f64)),
+            _ => Err(wrong_type(name, "a float")),
+        }
+    }
+
+    fn bool(&self, name: &str) -> Result<Option<bool>> {
+        match self.properties.get(name).map(|p| &p.value) {
+            None => Ok(None),
+            Some(serde_json::Value::Bool(value)) => Ok(Some(*value)),
+            Some(_) => Err(wrong_type(name, "a bool")),
+        }
+    }
+
+    fn string(&self, name: &str) -> Result<Option<&'a str>> {
+        match self.properties.get(name).map(|p| &p.value) {
+            None => Ok(None),
+            Some(serde_json::Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(wrong_type(name, "a string")),
+        }
+    }
+}
+
+/// Parses a Tiled color, written `#RRGGBB` or `#AARRGGBB`.
+fn parse_color(color: &str) -> Result<Color<u8>> {
+    let invalid = || malformed(format!("{color:?} is not a valid color"));
+    let digits = color.strip_prefix('#').ok_or_else(invalid)?;
+    let value = u32::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    let [alpha, red, green, blue] = value.to_be_bytes();
+    match digits.len() {
+        6 => Ok(Color::from_rgb(red, green, blue)),
+        8 => Ok(Color::from_rgba(red, green, blue, alpha)),
+        _ => Err(invalid()),
+    }
+}
+
+fn wrong_type(name: &str, expected: &str) -> ApplicationError {
+    malformed(format!("The property {name} should be {expected}"))
+}
+
+fn malformed(message: impl Into<String>) -> ApplicationError {
+    ApplicationError::new(message).with_kind(ErrorKind::MalformedLevel)
+}
+
+#[derive(Deserialize)]
+struct RawMap {
+    height: usize,
+    #[serde(rename = "tilewidth")]
+    tile_width: f32,
+    #[serde(rename = "tileheight")]
+    tile_height: f32,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    layers: Vec<RawLayer>,
+    #[serde(default)]
+    tilesets: Vec<RawTileset>,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum RawLayer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        name: String,
+        width: usize,
+        height: usize,
+        data: RawData,
+        #[serde(default)]
+        properties: Vec<RawProperty>,
+    },
+    #[serde(rename = "objectgroup")]
+    Objects {
+        #[serde(default)]
+        objects: Vec<RawObject>,
+        #[serde(default)]
+        properties: Vec<RawProperty>,
+    },
+    #[serde(rename = "group")]
+    Group {
+        #[serde(default)]
+        layers: Vec<RawLayer>,
+        #[serde(default)]
+        properties: Vec<RawProperty>,
+    },
+    /// Image layers, which have no tiles or objects.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawData {
+    Array(Vec<u32>),
+    Encoded(#[allow(dead_code)] String),
+}
+
+#[derive(Deserialize)]
+struct RawTileset {
+    #[serde(rename = "firstgid")]
+    first_global_id: u32,
+    source: Option<String>,
+    #[serde(default)]
+    tiles: Vec<RawTile>,
+}
+
+#[derive(Deserialize)]
+struct RawTile {
+    id: u32,
+    #[serde(default, rename = "type", alias = "class")]
+    kind: String,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type", alias = "class")]
+    kind: String,
+    #[serde(rename = "gid")]
+    global_id: Option<u32>,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+struct RawProperty {
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    value: serde_json::Value,
+}
+
+impl RawProperty {
+    fn value(&self) -> Result<TiledProperty> {
+        let value = match (self.kind.as_str(), &self.value) {
+            ("bool", serde_json::Value::Bool(value)) => Some(TiledProperty::Bool(*value)),
+            ("int" | "object", serde_json::Value::Number(value)) => {
+                value.as_i64().map(TiledProperty::Int)
+            }
+            ("float", serde_json::Value::Number(value)) => value.as_f64().map(TiledProperty::Float),
+            ("" | "string" | "color" | "file", serde_json::Value::String(value)) => {
+                Some(TiledProperty::String(value.clone()))
+            }
+            _ => None,
+        };
+        value.ok_or_else(|| {
+            malformed(format!(
+                "The property {} has an unsupported type or value",
+                self.name
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application_error::ErrorKind;
+    use crate::color::Color;
+    use crate::tile_coordinate::TileCoordinate;
+    use crate::tile_definition::TileFlags;
+    use crate::tiled::{TiledMap, TiledProperty};
+    use crate::units::si::length::{Length, pixel};
+    use crate::world::World;
+
+    const ROOM: &str = include_str!("../fixtures/tiled/room.json");
+    const UNSUPPORTED: &str = include_str!("../fixtures/tiled/unsupported.json");
+
+    fn world() -> World {
+        World::new(3, 4, Length::new::<pixel>(10f32))
+    }
+
+    #[test]
+    fn test_parse_tile_layers() {
+        let map = TiledMap::parse(ROOM).unwrap();
+        assert_eq!(TileCoordinate::new(-4, 0, 0), map.origin());
+        let [ground, upstairs] = map.layers() else {
+            panic!("Expected two tile layers");
+        };
+        assert_eq!(("ground", 0), (ground.name(), ground.level()));
+        assert_eq!((4, 3), (ground.columns(), ground.rows()));
+        // The top row comes first in the file.
+        assert_eq!(Some(1), ground.tile(0, 2));
+        assert_eq!(Some(5), ground.tile(1, 1));
+        assert_eq!(Some(2), ground.tile(3, 0));
+        // The group puts the layer on the level above.
+        assert_eq!(("upstairs", 1), (upstairs.name(), upstairs.level()));
+        // Empty, and flipped horizontally.
+        assert_eq!(None, upstairs.tile(0, 0));
+        assert_eq!(Some(3), upstairs.tile(3, 0));
+    }
+
+    #[test]
+    fn test_parse_tile_types_and_objects() {
+        let map = TiledMap::parse(ROOM).unwrap();
+        let [(wall_id, wall), (lava_id, lava)] = map.tile_types() else {
+            panic!("Expected two tile types");
+        };
+        assert_eq!((1, "brick wall"), (*wall_id, wall.name()));
+        assert!(!wall.is_traversable());
+        assert_eq!((5, "lava"), (*lava_id, lava.name()));
+        assert_eq!(Color::from_rgba(0xFF, 0x40, 0x00, 0x80), lava.color());
+        assert!((lava.movement_cost() - 3f32).abs() < f32::EPSILON);
+        assert!(lava.flags().contains(TileFlags::HAZARD | TileFlags::WATER));
+
+        let start = map.find_object("player start").unwrap();
+        assert_eq!(("spawn", 0), (start.kind(), start.level()));
+        // Its bottom edge is 17.5 pixels from the top of a map 30 pixels high.
+        assert!((start.x() - 0.5).abs() < f32::EPSILON);
+        assert!((start.y() - 1.25).abs() < f32::EPSILON);
+        assert_eq!(TileCoordinate::new(0, 1, 0), start.tile());
+        assert_eq!(
+            Some(&TiledProperty::String("south".to_string())),
+            start.property("facing")
+        );
+        let chest = map.find_object("chest").unwrap();
+        // Tile objects are anchored at their bottom edge.
+        assert!((chest.y() - 0.5).abs() < f32::EPSILON);
+        assert_eq!(1, chest.level());
+    }
+
+    #[test]
+    fn test_populate() {
+        let mut world = world();
+        TiledMap::parse(ROOM).unwrap().populate(&mut world);
+        assert_eq!(Some(1), world.get_tile(TileCoordinate::new(-4, 2, 0)));
+        assert_eq!(Some(2), world.get_tile(TileCoordinate::new(-1, 0, 0)));
+        assert_eq!(None, world.get_tile(TileCoordinate::new(-4, 0, 1)));
+        assert_eq!(Some(3), world.get_tile(TileCoordinate::new(-1, 0, 1)));
+        let wall = world
+            .get_tile_definition(TileCoordinate::new(-4, 2, 0))
+            .unwrap();
+        assert_eq!("brick wall", wall.name());
+        // Tiles the map does not describe keep their built-in definitions.
+        let stairs = world
+            .get_tile_definition(TileCoordinate::new(-1, 0, 0))
+            .unwrap();
+        assert_eq!(Some(1), stairs.level_change());
+    }
+
+    #[test]
+    fn test_malformed() {
+        for source in [
+            "",
+            "{}",
+            UNSUPPORTED,
+            &UNSUPPORTED.replace("\"base64\"", "\"csv\""),
+            &ROOM.replace("\"infinite\": false", "\"infinite\": true"),
+            &ROOM.replace("[2, 2, 2, 2,", "[2, 2, 2,"),
+            &ROOM.replace("\"#80ff4000\"", "\"red\""),
+            &ROOM.replace("\"value\": false", "\"value\": 0"),
+            &ROOM.replace("\"value\": 3 }", "\"value\": 0 }"),
+            &ROOM.replace("\"value\": 3 }", "\"value\": -2.5 }"),
+        ] {
+            let error = TiledMap::parse(source).unwrap_err();
+            assert_eq!(ErrorKind::MalformedLevel, error.kind(), "{source}");
+        }
+    }
+}
@@ -1,29 +1,52 @@
+use crate::collision::Collision;
 use crate::point_2d::Point2d;
 use crate::rectangle::Rectangle;
-use crate::tile_map::TileMap;
+use crate::tile_coordinate::TileCoordinate;
+use crate::tile_definition::TileDefinition;
 use crate::tile_map_coordinate::TileMapCoordinate;
-use crate::tile_map_key::TileMapKey;
+use crate::tile_registry::TileRegistry;
+use crate::tile_store::TileStore;
 use crate::units::si::length::{Length, pixel};
 use crate::world_coordinate::WorldCoordinate;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::collections::hash_map::Entry;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct World {
+    /// The size of a tile map, which world coordinates are counted in. Tiles themselves
+    /// are stored in chunks, so levels need not line up with tile maps.
     pub rows: usize,
     pub columns: usize,
-    pub tile_maps: HashMap<TileMapKey, TileMap>,
-    pub x_offset: Length,
-    pub y_offset: Length,
+    pub tiles: TileStore,
+    pub tile_types: TileRegistry,
     pub tile_size: Length,
 }
 
+/// How far, in pixels, a box moved by `World::move_and_slide` is kept from the faces it
+/// hits, so rounding cannot leave it inside a wall.
+const SKIN: f32 = 0.01;
+
+/// How many times `World::move_and_slide` changes direction along walls in one move. Each
+/// hit stops the move along one axis, so two is enough to come to rest in a corner.
+const MAX_SLIDES: usize = 2;
+
 impl World {
     pub const TILE_ROWS: usize = 9;
     pub const TILE_COLUMNS: usize = 17;
 
+    /// An empty world of tile maps with the given number of rows and columns of tiles
+    /// of the given size, using the default tile types.
+    #[must_use]
+    pub fn new(rows: usize, columns: usize, tile_size: Length) -> Self {
+        Self {
+            rows,
+            columns,
+            tiles: TileStore::default(),
+            tile_types: TileRegistry::new(),
+            tile_size,
+        }
+    }
+
     #[must_use]
     #[inline]
     pub fn rows(&self) -> usize {
@@ -42,16 +65,44 @@ impl World {
         self.tile_size
     }
 
-    pub fn add_tile_map(&mut self, id: TileMapKey) -> &mut TileMap {
-        let tile_map = TileMap::new(self.rows, self.columns);
-        let entry = self.tile_maps.entry(id);
-        match entry {
-            Entry::Occupied(o) => o.into_mut(),
-            Entry::Vacant(v) => v.insert(tile_map),
-        }
+    /// The bottom-left and top-right corners of the smallest rectangle holding every
+    /// tile on the level, or `None` if there are no tiles on it.
+    #[must_use]
+    pub fn bounds(&self, level: i32) -> Option<(WorldCoordinate, WorldCoordinate)> {
+        let (bottom_left, top_right) = self.tiles.bounds(level)?;
+        Some((
+            WorldCoordinate::from_tile(self, bottom_left),
+            WorldCoordinate::from_tile(self, top_right),
+        ))
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn get_tile(&self, tile: TileCoordinate) -> Option<u32> {
+        self.tiles.get(tile)
+    }
+
+    /// The definition of the tile, if it was set and its id is registered.
+    #[must_use]
+    #[inline]
+    pub fn get_tile_definition(&self, tile: TileCoordinate) -> Option<&TileDefinition> {
+        self.tile_types.get(self.tiles.get(tile)?)
+    }
+
+    #[inline]
+    pub fn set_tile(&mut self, tile: TileCoordinate, value: u32) {
+        self.tiles.set(tile, value);
     }
 
-    /// Indicates whether all four corners of the rectangle fall within a traversable tile.
+    /// The level the tile leads to, if it is stairs or a ladder.
+    #[must_use]
+    pub fn stairs_destination(&self, tile: TileCoordinate) -> Option<i32> {
+        let level_change = self.get_tile_definition(tile)?.level_change()?;
+        Some(tile.z() + level_change)
+    }
+
+    /// Indicates whether all four corners of the rectangle fall within a traversable tile
+    /// on the coordinate's level.
     #[must_use]
     pub fn is_traversable(&self, coordinate: &WorldCoordinate, bounds: Rectangle<f32>) -> bool {
         if !self.is_traversable_coordinate(coordinate) {
@@ -69,18 +120,153 @@ impl World {
         self.is_traversable_coordinate(&top_right)
     }
 
+    /// The first tile the rectangle hits when its bottom-left corner is moved from the
+    /// coordinate by the delta, in pixels, or `None` if nothing is in the way. Tiles the
+    /// rectangle already overlaps are ignored, so it can always move out of them.
     #[must_use]
-    fn is_traversable_coordinate(&self, coordinate: &WorldCoordinate) -> bool {
-        self.tile_maps
-            .get(&coordinate.tile_map_key())
-            .and_then(|m| m.get(coordinate.tile_y(), coordinate.tile_x()))
-            .is_some_and(|t| t == 0)
+    pub fn sweep(
+        &self,
+        coordinate: &WorldCoordinate,
+        bounds: Rectangle<f32>,
+        delta: Point2d<f32>,
+    ) -> Option<Collision> {
+        self.sweep_from(coordinate, bounds, Point2d::from_x_y(0f32, 0f32), delta)
     }
 
+    /// Moves the rectangle's bottom-left corner from the coordinate by the delta, in
+    /// pixels, as far as it can go. When it hits a wall, the part of the move into the
+    /// wall is dropped and the rest carries it along the wall, so it slides rather than
+    /// sticking. It cannot pass through walls, however far it moves.
     #[must_use]
-    #[inline]
-    pub fn get_tile_map(&self, key: TileMapKey) -> Option<&TileMap> {
-        self.tile_maps.get(&key)
+    pub fn move_and_slide(
+        &self,
+        coordinate: &WorldCoordinate,
+        bounds: Rectangle<f32>,
+        delta: Point2d<f32>,
+    ) -> WorldCoordinate {
+        // Measure everything from the starting coordinate to avoid rounding each step.
+        let (mut moved_x, mut moved_y) = (0f32, 0f32);
+        let (mut delta_x, mut delta_y) = (delta.x(), delta.y());
+        for _ in 0..MAX_SLIDES {
+            if delta_x == 0f32 && delta_y == 0f32 {
+                break;
+            }
+            let offset = Point2d::from_x_y(moved_x, moved_y);
+            let step = Point2d::from_x_y(delta_x, delta_y);
+            let Some(collision) = self.sweep_from(coordinate, bounds, offset, step) else {
+                moved_x += delta_x;
+                moved_y += delta_y;
+                break;
+            };
+            let time = collision.time();
+            let normal = collision.normal();
+            moved_x += delta_x * time + normal.x() * SKIN;
+            moved_y += delta_y * time + normal.y() * SKIN;
+            // Whatever is left of the move carries on along the wall.
+            delta_x *= (1f32 - time) * (1f32 - normal.x().abs());
+            delta_y *= (1f32 - time) * (1f32 - normal.y().abs());
+        }
+        coordinate.shifted(moved_x, moved_y)
+    }
+
+    /// Sweeps the rectangle with its bottom-left corner offset from the coordinate.
+    fn sweep_from(
+        &self,
+        coordinate: &WorldCoordinate,
+        bounds: Rectangle<f32>,
+        offset: Point2d<f32>,
+        delta: Point2d<f32>,
+    ) -> Option<Collision> {
+        if delta.x() == 0f32 && delta.y() == 0f32 {
+            return None;
+        }
+        let (left, bottom) = (offset.x(), offset.y());
+        let (right, top) = (left + bounds.width(), bottom + bounds.height());
+        // Every tile the rectangle passes over on its way.
+        let first = coordinate
+            .shifted(left + delta.x().min(0f32), bottom + delta.y().min(0f32))
+            .tile();
+        let last = coordinate
+            .shifted(right + delta.x().max(0f32), top + delta.y().max(0f32))
+            .tile();
+        let tile_size = self.tile_size.get::<pixel>();
+        let mut earliest: Option<Collision> = None;
+        for y in first.y()..=last.y() {
+            for x in first.x()..=last.x() {
+                let tile = TileCoordinate::new(x, y, first.z());
+                if self
+                    .get_tile_definition(tile)
+                    .is_some_and(TileDefinition::is_traversable)
+                {
+                    continue;
+                }
+                let tile_left_bottom =
+                    WorldCoordinate::from_tile(self, tile).offset_from(coordinate);
+                let tile_left = tile_left_bottom.x();
+                let tile_bottom = tile_left_bottom.y();
+                let Some((entry_x, exit_x)) =
+                    Self::overlap_times(left, right, tile_left, tile_left + tile_size, delta.x())
+                else {
+                    continue;
+                };
+                let Some((entry_y, exit_y)) = Self::overlap_times(
+                    bottom,
+                    top,
+                    tile_bottom,
+                    tile_bottom + tile_size,
+                    delta.y(),
+                ) else {
+                    continue;
+                };
+                let entry = entry_x.max(entry_y);
+                let exit = exit_x.min(exit_y);
+                if entry >= exit || entry > 1f32 || exit <= 0f32 {
+                    continue;
+                }
+                // The face hit is on the axis the rectangle reached last.
+                let normal = if entry_x >= entry_y {
+                    Point2d::from_x_y(-delta.x().signum(), 0f32)
+                } else {
+                    Point2d::from_x_y(0f32, -delta.y().signum())
+                };
+                // Starting inside the tile, rather than just touching it.
+                let overlap = entry * (normal.x() * delta.x() + normal.y() * delta.y());
+                if overlap > SKIN {
+                    continue;
+                }
+                let impact = entry.max(0f32);
+                if earliest.is_none_or(|c| impact < c.time()) {
+                    earliest = Some(Collision::new(impact, normal));
+                }
+            }
+        }
+        earliest
+    }
+
+    /// The fractions of a move when a span first and last overlaps another along one
+    /// axis. The times are infinite if it overlaps throughout, or `None` if it never does.
+    fn overlap_times(
+        start: f32,
+        end: f32,
+        other_start: f32,
+        other_end: f32,
+        delta: f32,
+    ) -> Option<(f32, f32)> {
+        if delta > 0f32 {
+            Some(((other_start - end) / delta, (other_end - start) / delta))
+        } else if delta < 0f32 {
+            Some(((other_end - start) / delta, (other_start - end) / delta))
+        } else if end > other_start && start < other_end {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    fn is_traversable_coordinate(&self, coordinate: &WorldCoordinate) -> bool {
+        self.get_tile_definition(coordinate.tile())
+            .is_some_and(TileDefinition::is_traversable)
     }
 
     #[must_use]
@@ -112,3 +298,144 @@ impl World {
         (tile_x, tile_y)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::point_2d::Point2d;
+    use crate::rectangle::Rectangle;
+    use crate::tile_coordinate::TileCoordinate;
+    use crate::tile_registry::TileRegistry;
+    use crate::units::si::length::{Length, pixel};
+    use crate::world::World;
+    use crate::world_coordinate::WorldCoordinate;
+
+    #[test]
+    fn test_levels() {
+        let mut world = World::new(3, 4, Length::new::<pixel>(10f32));
+        world.set_tile(TileCoordinate::new(0, 0, 0), TileRegistry::FLOOR);
+        world.set_tile(TileCoordinate::new(1, 0, 0), TileRegistry::STAIRS_UP);
+        world.set_tile(TileCoordinate::new(1, 0, 1), TileRegistry::STAIRS_DOWN);
+        world.set_tile(TileCoordinate::new(0, 0, 1), TileRegistry::WALL);
+
+        let bounds = Rectangle::new(0f32, 0f32, 2f32, 2f32);
+        let downstairs = WorldCoordinate::from_tile(&world, TileCoordinate::new(0, 0, 0));
+        let upstairs = downstairs.on_level(1);
+        assert!(world.is_traversable(&downstairs.shifted(3f32, 3f32), bounds));
+        assert!(!world.is_traversable(&upstairs.shifted(3f32, 3f32), bounds));
+        assert!(world.is_traversable(&upstairs.shifted(13f32, 3f32), bounds));
+
+        assert_eq!(None, world.stairs_destination(TileCoordinate::new(0, 0, 0)));
+        assert_eq!(
+            Some(1),
+            world.stairs_destination(TileCoordinate::new(1, 0, 0))
+        );
+        assert_eq!(
+            Some(0),
+            world.stairs_destination(TileCoordinate::new(1, 0, 1))
+        );
+        assert_eq!(
+            Some(TileCoordinate::new(1, 0, 1)),
+            world
+                .bounds(1)
+                .map(|(_, top_right)| top_right.tile().shifted(-1, -1))
+        );
+    }
+
+    /// A 6x5 room of 10 pixel tiles, walled in, with a pillar one tile in from its
+    /// bottom-left corner. Returns the world and the bottom-left corner of the room's
+    /// bottom-left tile.
+    fn room() -> (World, WorldCoordinate) {
+        let mut world = World::new(3, 4, Length::new::<pixel>(10f32));
+        for x in 0..6 {
+            for y in 0..5 {
+                let wall = x == 0 || y == 0 || x == 5 || y == 4 || (x, y) == (2, 2);
+                let tile = if wall {
+                    TileRegistry::WALL
+                } else {
+                    TileRegistry::FLOOR
+                };
+                world.set_tile(TileCoordinate::new(x, y, 0), tile);
+            }
+        }
+        let origin = WorldCoordinate::from_tile(&world, TileCoordinate::new(0, 0, 0));
+        (world, origin)
+    }
+
+    fn assert_near(expected: (f32, f32), actual: Point2d<f32>) {
+        let close = |a: f32, b: f32| (a - b).abs() < 0.05;
+        assert!(
+            close(expected.0, actual.x()) && close(expected.1, actual.y()),
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn test_sweep() {
+        let (world, origin) = room();
+        let bounds = Rectangle::new(0f32, 0f32, 4f32, 4f32);
+        let collision = world
+            .sweep(
+                &origin.shifted(12f32, 22f32),
+                bounds,
+                Point2d::from_x_y(20f32, 0f32),
+            )
+            .unwrap();
+        // The right edge, at 16, reaches the pillar at 20 after a fifth of the move.
+        assert!((collision.time() - 0.2).abs() < 1e-4);
+        assert_near((-1f32, 0f32), collision.normal());
+
+        let start = origin.shifted(12f32, 12f32);
+        let collision = world
+            .sweep(&start, bounds, Point2d::from_x_y(0f32, -8f32))
+            .unwrap();
+        assert!((collision.time() - 0.25).abs() < 1e-4);
+        assert_near((0f32, 1f32), collision.normal());
+
+        assert!(
+            world
+                .sweep(&start, bounds, Point2d::from_x_y(4f32, -1f32))
+                .is_none()
+        );
+        assert!(
+            world
+                .sweep(&start, bounds, Point2d::from_x_y(0f32, 0f32))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_move_and_slide() {
+        let (world, origin) = room();
+        let bounds = Rectangle::new(0f32, 0f32, 4f32, 4f32);
+
+        // Nothing in the way.
+        let start = origin.shifted(12f32, 12f32);
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(3f32, -1f32));
+        assert_near((15f32, 11f32), end.offset_from(&origin));
+
+        // Moving diagonally into the floor slides along it instead of stopping.
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(-1f32, -6f32));
+        assert_near((11f32, 10f32), end.offset_from(&origin));
+
+        // Into a corner, it stops against both walls.
+        let start = origin.shifted(42f32, 32f32);
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(30f32, 30f32));
+        assert_near((46f32, 36f32), end.offset_from(&origin));
+        assert!(world.is_traversable(&end, bounds));
+
+        // Moving fast enough to skip over the pillar in one frame still stops at it.
+        let start = origin.shifted(32f32, 22f32);
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(-500f32, 0f32));
+        assert_near((30f32, 22f32), end.offset_from(&origin));
+        let start = origin.shifted(22f32, 32f32);
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(0f32, -500f32));
+        assert_near((22f32, 30f32), end.offset_from(&origin));
+
+        // Already touching a wall, it can still slide along it and move away from it.
+        let start = origin.shifted(10f32, 25f32);
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(-3f32, 4f32));
+        assert_near((10f32, 29f32), end.offset_from(&origin));
+        let end = world.move_and_slide(&start, bounds, Point2d::from_x_y(2f32, 0f32));
+        assert_near((12f32, 25f32), end.offset_from(&origin));
+    }
+}
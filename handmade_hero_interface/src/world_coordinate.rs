@@ -1,4 +1,5 @@
 use crate::point_2d::Point2d;
+use crate::tile_coordinate::TileCoordinate;
 use crate::tile_map_coordinate::TileMapCoordinate;
 use crate::tile_map_key::TileMapKey;
 use crate::units::si::length::{Length, pixel};
@@ -34,6 +35,26 @@ impl WorldCoordinate {
         )
     }
 
+    /// The bottom-left corner of the tile.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn from_tile(world: &World, tile: TileCoordinate) -> Self {
+        let columns = world.columns() as i32;
+        let rows = world.rows() as i32;
+        let key = TileMapKey {
+            x: tile.x().div_euclid(columns) as isize,
+            y: tile.y().div_euclid(rows) as isize,
+            z: tile.z(),
+        };
+        #[allow(clippy::cast_sign_loss)]
+        let tile_map_coordinate = TileMapCoordinate::at_x_y(
+            tile.x().rem_euclid(columns) as usize,
+            tile.y().rem_euclid(rows) as usize,
+        );
+        Self::new(world, key, tile_map_coordinate)
+    }
+
     #[inline]
     #[must_use]
     fn new_internal(
@@ -70,6 +91,13 @@ impl WorldCoordinate {
         self.tile_map_key.y()
     }
 
+    /// The level, or floor, the coordinate is on.
+    #[inline]
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.tile_map_key.z()
+    }
+
     #[inline]
     #[must_use]
     pub fn tile_x(&self) -> usize {
@@ -82,6 +110,22 @@ impl WorldCoordinate {
         self.tile_map_coordinate.y()
     }
 
+    /// The tile holding this coordinate, counted from the world's origin. Tiles beyond
+    /// the range of `TileCoordinate` are clamped to its edge.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn tile(&self) -> TileCoordinate {
+        let saturate = |tile_map: isize, size: usize, tile: usize| {
+            let tile = tile_map
+                .saturating_mul(size as isize)
+                .saturating_add(tile as isize);
+            i32::try_from(tile).unwrap_or(if tile < 0 { i32::MIN } else { i32::MAX })
+        };
+        let x = saturate(self.tile_map_x(), self.columns, self.tile_x());
+        let y = saturate(self.tile_map_y(), self.rows, self.tile_y());
+        TileCoordinate::new(x, y, self.level())
+    }
+
     #[inline]
     #[must_use]
     pub fn tile_offset(&self) -> Point2d<f32> {
@@ -122,6 +166,7 @@ impl WorldCoordinate {
         let new_tile_map_key = TileMapKey {
             x: tile_map_x,
             y: tile_map_y,
+            z: self.tile_map_key.z(),
         };
         let new_tile_offset = Point2d::from_x_y(tile_offset_x, tile_offset_y);
         let new_tile_coordinate = TileMapCoordinate::at_x_y_offset(tile_x, tile_y, new_tile_offset);
@@ -134,6 +179,67 @@ impl WorldCoordinate {
         )
     }
 
+    /// The same position on another level.
+    #[must_use]
+    pub fn on_level(&self, level: i32) -> WorldCoordinate {
+        let key = TileMapKey {
+            z: level,
+            ..self.tile_map_key
+        };
+        WorldCoordinate::new_internal(
+            key,
+            self.tile_map_coordinate.clone(),
+            self.rows,
+            self.columns,
+            self.tile_size,
+        )
+    }
+
+    /// The distance, in pixels, from the origin to this coordinate, ignoring the levels
+    /// they are on.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn offset_from(&self, origin: &WorldCoordinate) -> Point2d<f32> {
+        // Count whole tiles separately from the offsets within them, so coordinates far
+        // from the origin tile map do not lose precision.
+        let tile_size = self.tile_size.get::<pixel>();
+        let tiles_x = (self.tile_map_x() - origin.tile_map_x()) * self.columns as isize
+            + self.tile_x() as isize
+            - origin.tile_x() as isize;
+        let tiles_y = (self.tile_map_y() - origin.tile_map_y()) * self.rows as isize
+            + self.tile_y() as isize
+            - origin.tile_y() as isize;
+        let offset = self.tile_offset();
+        let origin_offset = origin.tile_offset();
+        Point2d::from_x_y(
+            tiles_x as f32 * tile_size + offset.x() - origin_offset.x(),
+            tiles_y as f32 * tile_size + offset.y() - origin_offset.y(),
+        )
+    }
+
+    /// The bottom-left corner of the tile map holding this coordinate.
+    #[must_use]
+    pub fn tile_map_origin(&self) -> WorldCoordinate {
+        WorldCoordinate::new_internal(
+            self.tile_map_key,
+            TileMapCoordinate::at_x_y(0, 0),
+            self.rows,
+            self.columns,
+            self.tile_size,
+        )
+    }
+
+    /// The middle of the tile map holding this coordinate.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn tile_map_center(&self) -> WorldCoordinate {
+        let tile_size = self.tile_size.get::<pixel>();
+        let width = self.columns as f32 * tile_size;
+        let height = self.rows as f32 * tile_size;
+        self.tile_map_origin().shifted(width / 2f32, height / 2f32)
+    }
+
     #[must_use]
     #[allow(clippy::cast_precision_loss)]
     #[allow(clippy::cast_possible_wrap)]
@@ -155,24 +261,14 @@ impl WorldCoordinate {
         if tile_offset < 0f32 {
             let delta_tile = tile_offset / tile_size;
             tile = f32::floor(tile as f32 + delta_tile) as isize;
-            tile_offset %= tile_size;
-            tile_offset += tile_size;
+            tile_offset = tile_offset.rem_euclid(tile_size);
         } else if tile_offset >= tile_size {
             let delta_tile = tile_offset / tile_size;
             tile = f32::floor(tile as f32 + delta_tile) as isize;
             tile_offset %= tile_size;
         }
-        let mut tile_map = tile_map;
-        if tile < 0 {
-            let delta_tile_map = tile as f32 / max_tiles as f32;
-            tile_map = f32::floor(tile_map as f32 + delta_tile_map) as isize;
-            tile %= max_tiles as isize;
-            tile += max_tiles as isize;
-        } else if tile >= max_tiles as isize {
-            let delta_tile_map_x = tile as f32 / max_tiles as f32;
-            tile_map = f32::floor(tile_map as f32 + delta_tile_map_x) as isize;
-            tile %= max_tiles as isize;
-        }
+        let tile_map = tile_map + tile.div_euclid(max_tiles as isize);
+        let tile = tile.rem_euclid(max_tiles as isize);
         ShiftedCoordinate {
             tile_offset,
             tile: tile as usize,
@@ -0,0 +1,392 @@
+//! Generates a level from a seed: a grid of rooms, one tile map each, walled in and
+//! joined by doors. Every room can be reached from every other, and each level above
+//! the first is reached by stairs from the level below. The same seed always generates
+//! the same level.
+
+use crate::application_error::{ApplicationError, Result};
+use crate::level::{Level, LevelTileMap};
+use crate::random::Random;
+use crate::tile_map_key::TileMapKey;
+use crate::tile_registry::TileRegistry;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldGenerator {
+    seed: u64,
+    rooms_wide: usize,
+    rooms_high: usize,
+    levels: usize,
+    door_width: usize,
+}
+
+impl WorldGenerator {
+    /// A generator for a single level of 3x3 rooms, with doors three tiles wide.
+    #[inline]
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rooms_wide: 3,
+            rooms_high: 3,
+            levels: 1,
+            door_width: 3,
+        }
+    }
+
+    /// The number of rooms across and up each level. The bottom-left room is at tile
+    /// map `(0, 0)`.
+    #[inline]
+    #[must_use]
+    pub fn with_rooms(mut self, rooms_wide: usize, rooms_high: usize) -> Self {
+        self.rooms_wide = rooms_wide;
+        self.rooms_high = rooms_high;
+        self
+    }
+
+    /// The number of levels, starting at level 0 and going up.
+    #[inline]
+    #[must_use]
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_door_width(mut self, door_width: usize) -> Self {
+        self.door_width = door_width;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Generates rooms the size of the world's tile maps.
+    ///
+    /// # Errors
+    /// An error is returned if there are no rooms or levels, or the rooms are too small
+    /// to hold a door between their corners or stairs away from their walls.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn generate(&self, columns: usize, rows: usize) -> Result<Level> {
+        if self.rooms_wide == 0 || self.rooms_high == 0 || self.levels == 0 {
+            return Err(ApplicationError::new(format!(
+                "Cannot generate {} levels of {}x{} rooms",
+                self.levels, self.rooms_wide, self.rooms_high
+            )));
+        }
+        let smallest = usize::max(self.door_width + 2, 5);
+        if self.door_width == 0 || columns < smallest || rows < smallest {
+            return Err(ApplicationError::new(format!(
+                "Cannot generate {columns}x{rows} rooms with doors {} tiles wide",
+                self.door_width
+            )));
+        }
+        let mut generation = Generation {
+            generator: self,
+            random: Random::new(self.seed),
+            columns,
+            rows,
+        };
+        let mut rooms = (0..self.levels)
+            .map(|_| generation.generate_rooms())
+            .collect::<Vec<_>>();
+        for level in 1..self.levels {
+            let (below, above) = rooms.split_at_mut(level);
+            generation.add_stairs(&mut below[level - 1], &mut above[0]);
+        }
+        let mut tile_maps = Vec::with_capacity(self.levels * self.rooms_wide * self.rooms_high);
+        for (level, rooms) in rooms.into_iter().enumerate() {
+            for (index, tiles) in rooms.into_iter().enumerate() {
+                let key = TileMapKey {
+                    x: (index % self.rooms_wide) as isize,
+                    y: (index / self.rooms_wide) as isize,
+                    z: level as i32,
+                };
+                tile_maps.push(LevelTileMap::new(key, columns, rows, tiles)?);
+            }
+        }
+        Ok(Level::new(tile_maps))
+    }
+}
+
+/// The state of one call to `WorldGenerator::generate`.
+struct Generation<'a> {
+    generator: &'a WorldGenerator,
+    random: Random,
+    columns: usize,
+    rows: usize,
+}
+
+impl Generation<'_> {
+    /// The tiles of every room on a level, bottom row first, walled in and with doors
+    /// between them. The rooms are joined by a random maze, so they are all connected,
+    /// with a few extra doors so there is more than one way around.
+    fn generate_rooms(&mut self) -> Vec<Vec<u32>> {
+        let (wide, high) = (self.generator.rooms_wide, self.generator.rooms_high);
+        // The door between each room and the room to its east or north, as the row or
+        // column its bottom or left tile is at, if there is one.
+        let mut east_doors = vec![None; wide * high];
+        let mut north_doors = vec![None; wide * high];
+        let mut visited = vec![false; wide * high];
+        let mut path = vec![0];
+        visited[0] = true;
+        while let Some(&room) = path.last() {
+            let (x, y) = (room % wide, room / wide);
+            let mut neighbors = Vec::with_capacity(4);
+            if x > 0 && !visited[room - 1] {
+                neighbors.push(room - 1);
+            }
+            if x + 1 < wide && !visited[room + 1] {
+                neighbors.push(room + 1);
+            }
+            if y > 0 && !visited[room - wide] {
+                neighbors.push(room - wide);
+            }
+            if y + 1 < high && !visited[room + wide] {
+                neighbors.push(room + wide);
+            }
+            if neighbors.is_empty() {
+                path.pop();
+                continue;
+            }
+            let next = neighbors[self.below(neighbors.len())];
+            if next / wide == y {
+                east_doors[room.min(next)] = Some(self.door_position(self.rows));
+            } else {
+                north_doors[room.min(next)] = Some(self.door_position(self.columns));
+            }
+            visited[next] = true;
+            path.push(next);
+        }
+        for room in 0..wide * high {
+            let (x, y) = (room % wide, room / wide);
+            if x + 1 < wide && east_doors[room].is_none() && self.below(4) == 0 {
+                east_doors[room] = Some(self.door_position(self.rows));
+            }
+            if y + 1 < high && north_doors[room].is_none() && self.below(4) == 0 {
+                north_doors[room] = Some(self.door_position(self.columns));
+            }
+        }
+
+        let (columns, rows) = (self.columns, self.rows);
+        (0..wide * high)
+            .map(|room| {
+                let mut tiles = vec![TileRegistry::FLOOR; columns * rows];
+                for column in 0..columns {
+                    tiles[column] = TileRegistry::WALL;
+                    tiles[(rows - 1) * columns + column] = TileRegistry::WALL;
+                }
+                for row in 0..rows {
+                    tiles[row * columns] = TileRegistry::WALL;
+                    tiles[row * columns + columns - 1] = TileRegistry::WALL;
+                }
+                let (x, y) = (room % wide, room / wide);
+                let west = if x > 0 { east_doors[room - 1] } else { None };
+                let south = if y > 0 {
+                    north_doors[room - wide]
+                } else {
+                    None
+                };
+                // The rooms are the same size, so a door lines up with the one it opens
+                // onto in the next room.
+                for offset in 0..self.generator.door_width {
+                    if let Some(row) = west {
+                        tiles[(row + offset) * columns] = TileRegistry::FLOOR;
+                    }
+                    if let Some(row) = east_doors[room] {
+                        tiles[(row + offset) * columns + columns - 1] = TileRegistry::FLOOR;
+                    }
+                    if let Some(column) = south {
+                        tiles[column + offset] = TileRegistry::FLOOR;
+                    }
+                    if let Some(column) = north_doors[room] {
+                        tiles[(rows - 1) * columns + column + offset] = TileRegistry::FLOOR;
+                    }
+                }
+                tiles
+            })
+            .collect()
+    }
+
+    /// Puts stairs up in a random room of the level below, landing on stairs down in the
+    /// same spot on the level above. They are kept a tile away from the walls so there
+    /// is room to step off them.
+    fn add_stairs(&mut self, below: &mut [Vec<u32>], above: &mut [Vec<u32>]) {
+        let room = self.below(below.len());
+        let column = 2 + self.below(self.columns - 4);
+        let row = 2 + self.below(self.rows - 4);
+        let mut tile = row * self.columns + column;
+        // Keep the stairs down onto the level below, if they are in the same spot.
+        if below[room][tile] == TileRegistry::STAIRS_DOWN {
+            tile -= 1;
+        }
+        below[room][tile] = TileRegistry::STAIRS_UP;
+        above[room][tile] = TileRegistry::STAIRS_DOWN;
+    }
+
+    /// Where to put the first tile of a door in a wall of the length, keeping the door
+    /// clear of the corners.
+    fn door_position(&mut self, length: usize) -> usize {
+        1 + self.below(length - self.generator.door_width - 1)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        let bound = u32::try_from(bound).unwrap_or(u32::MAX);
+        self.random.below(bound) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tile_coordinate::TileCoordinate;
+    use crate::tile_definition::TileDefinition;
+    use crate::units::si::length::{Length, pixel};
+    use crate::world::World;
+    use crate::world_generator::WorldGenerator;
+    use std::collections::{HashSet, VecDeque};
+
+    const COLUMNS: usize = 17;
+    const ROWS: usize = 9;
+
+    fn generate(generator: &WorldGenerator) -> World {
+        let mut world = World::new(ROWS, COLUMNS, Length::new::<pixel>(10f32));
+        generator
+            .generate(COLUMNS, ROWS)
+            .unwrap()
+            .populate(&mut world);
+        world
+    }
+
+    fn is_traversable(world: &World, tile: TileCoordinate) -> bool {
+        world
+            .get_tile_definition(tile)
+            .is_some_and(TileDefinition::is_traversable)
+    }
+
+    #[test]
+    fn test_same_seed_same_level() {
+        let generator = WorldGenerator::new(1234).with_rooms(4, 3).with_levels(2);
+        assert_eq!(
+            generator.generate(COLUMNS, ROWS).unwrap(),
+            generator.generate(COLUMNS, ROWS).unwrap()
+        );
+        assert_ne!(
+            generator.generate(COLUMNS, ROWS).unwrap(),
+            WorldGenerator::new(1235)
+                .with_rooms(4, 3)
+                .with_levels(2)
+                .generate(COLUMNS, ROWS)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    fn test_rooms_are_walled_in_and_connected() {
+        for seed in 0..20 {
+            let world = generate(&WorldGenerator::new(seed).with_rooms(4, 3).with_levels(3));
+            let width = 4 * COLUMNS as i32;
+            let height = 3 * ROWS as i32;
+            let mut floor = HashSet::new();
+            for level in 0..3 {
+                let (bottom_left, top_right) = world.tiles.bounds(level).unwrap();
+                assert_eq!(TileCoordinate::new(0, 0, level), bottom_left);
+                assert_eq!(TileCoordinate::new(width, height, level), top_right);
+                for x in 0..width {
+                    for y in 0..height {
+                        let tile = TileCoordinate::new(x, y, level);
+                        let edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                        if is_traversable(&world, tile) {
+                            assert!(!edge, "{tile:?} leads out of the world");
+                            floor.insert(tile);
+                        }
+                    }
+                }
+            }
+
+            // Walk from the first room, taking every door and stairs.
+            let start = TileCoordinate::new(1, 1, 0);
+            let mut reached = HashSet::from([start]);
+            let mut queue = VecDeque::from([start]);
+            while let Some(tile) = queue.pop_front() {
+                let mut next = vec![
+                    tile.shifted(1, 0),
+                    tile.shifted(-1, 0),
+                    tile.shifted(0, 1),
+                    tile.shifted(0, -1),
+                ];
+                if let Some(level) = world.stairs_destination(tile) {
+                    next.push(tile.on_level(level));
+                }
+                for tile in next {
+                    if floor.contains(&tile) && reached.insert(tile) {
+                        queue.push_back(tile);
+                    }
+                }
+            }
+            assert_eq!(floor.len(), reached.len(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_doors_line_up() {
+        let world = generate(&WorldGenerator::new(99).with_rooms(2, 2).with_door_width(2));
+        let mut doors = 0;
+        for y in 0..2 * ROWS {
+            let y = i32::try_from(y).unwrap();
+            let west = is_traversable(&world, TileCoordinate::new(16, y, 0));
+            let east = is_traversable(&world, TileCoordinate::new(17, y, 0));
+            assert_eq!(west, east, "row {y}");
+            doors += usize::from(west);
+        }
+        for x in 0..2 * COLUMNS {
+            let x = i32::try_from(x).unwrap();
+            let south = is_traversable(&world, TileCoordinate::new(x, 8, 0));
+            let north = is_traversable(&world, TileCoordinate::new(x, 9, 0));
+            assert_eq!(south, north, "column {x}");
+            doors += usize::from(south);
+        }
+        // Four rooms need at least three doors to be connected.
+        assert!(doors >= 3 * 2, "{doors}");
+        assert_eq!(0, doors % 2);
+    }
+
+    #[test]
+    fn test_invalid_generators() {
+        assert!(
+            WorldGenerator::new(0)
+                .with_rooms(0, 3)
+                .generate(17, 9)
+                .is_err()
+        );
+        assert!(
+            WorldGenerator::new(0)
+                .with_levels(0)
+                .generate(17, 9)
+                .is_err()
+        );
+        assert!(
+            WorldGenerator::new(0)
+                .with_door_width(0)
+                .generate(17, 9)
+                .is_err()
+        );
+        assert!(
+            WorldGenerator::new(0)
+                .with_door_width(8)
+                .generate(17, 9)
+                .is_err()
+        );
+        assert!(WorldGenerator::new(0).generate(4, 9).is_err());
+        assert!(
+            WorldGenerator::new(0)
+                .with_rooms(1, 1)
+                .generate(5, 5)
+                .is_ok()
+        );
+    }
+}
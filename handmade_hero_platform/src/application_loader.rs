@@ -0,0 +1,253 @@
+use handmade_hero_interface::application::Application;
+use handmade_hero_interface::application_error::{ApplicationError, ErrorKind, Result};
+use handmade_hero_interface::audio_context::AudioContext;
+use handmade_hero_interface::initialize_context::InitializeContext;
+use handmade_hero_interface::input_context::InputContext;
+use handmade_hero_interface::plugin_abi::PluginAbi;
+use handmade_hero_interface::render_context::RenderContext;
+use libloading::{Library, Symbol, library_filename};
+use std::ffi::OsString;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub struct ApplicationStub {
+    application: Box<dyn Application>,
+    // NOTE: Ensure _library appears after application, so these fields get dropped
+    // in the correct order!
+    _library: Library,
+}
+
+impl Debug for ApplicationStub {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApplicationStub").finish_non_exhaustive()
+    }
+}
+
+impl Application for ApplicationStub {
+    #[inline]
+    fn initialize(&self, context: InitializeContext<'_>) -> Result<()> {
+        self.application.initialize(context)
+    }
+
+    #[inline]
+    fn process_input(&self, context: InputContext<'_>) {
+        self.application.process_input(context);
+    }
+
+    #[inline]
+    fn render(&self, context: RenderContext<'_>) {
+        self.application.render(context);
+    }
+
+    #[inline]
+    fn write_sound(&self, context: AudioContext<'_>) {
+        self.application.write_sound(context);
+    }
+}
+
+/// Loads the `handmade_hero_plugin` dynamic library (a DLL on Windows, a shared object
+/// on Linux), reloading it whenever the library on disk is rebuilt.
+///
+/// The library is never loaded from where it is built. Instead, it is copied to a
+/// "running" file with a counter suffix and loaded from there, leaving the original
+/// free to be overwritten by the compiler. Each attempt copies to a new counter so the
+/// library that is still mapped into memory is never written to.
+///
+/// Before calling into a library, its `plugin_abi` descriptor is compared with the
+/// host's. If a rebuilt plugin cannot be copied or opened (e.g., the compiler is still
+/// writing it), the failure is reported to the error handler, the previously loaded
+/// plugin keeps running, and the load is retried on the next frame. Incompatible
+/// plugins are not retried until they are rebuilt again.
+pub struct ApplicationLoader {
+    plugin_directory: PathBuf,
+    running_count: usize,
+    last_modified: Option<SystemTime>,
+    last_error: Option<String>,
+    stub: Option<ApplicationStub>,
+    error_handler: Box<dyn FnMut(&ApplicationError)>,
+}
+
+impl Debug for ApplicationLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApplicationLoader")
+            .field("plugin_directory", &self.plugin_directory)
+            .field("running_count", &self.running_count)
+            .field("last_modified", &self.last_modified)
+            .field("last_error", &self.last_error)
+            .field("stub", &self.stub)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ApplicationLoader {
+    #[inline]
+    #[must_use]
+    pub fn new(plugin_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            plugin_directory: plugin_directory.into(),
+            running_count: 0,
+            last_modified: None,
+            last_error: None,
+            stub: None,
+            error_handler: Box::new(|e| eprintln!("{e}")),
+        }
+    }
+
+    /// Replaces the handler notified when a rebuilt plugin fails to load. By default,
+    /// errors are written to standard error. Repeated failures with the same message
+    /// are only reported once.
+    #[inline]
+    pub fn set_error_handler(&mut self, handler: impl FnMut(&ApplicationError) + 'static) {
+        self.error_handler = Box::new(handler);
+    }
+
+    /// Returns the most recently loaded plugin, loading a new copy first if the library
+    /// on disk has changed since the last successful load.
+    ///
+    /// # Errors
+    /// An error is returned only if no plugin has been loaded successfully yet. Once a
+    /// plugin is running, failures are passed to the error handler instead.
+    pub fn load(&mut self, context: InitializeContext<'_>) -> Result<&mut ApplicationStub> {
+        match self.reload_if_modified(context) {
+            Ok(()) => self.last_error = None,
+            Err(error) if self.stub.is_some() => self.report(&error),
+            Err(error) => return Err(error),
+        }
+        self.stub
+            .as_mut()
+            .ok_or_else(|| ApplicationError::new("No application plugin is loaded"))
+    }
+
+    fn reload_if_modified(&mut self, context: InitializeContext<'_>) -> Result<()> {
+        let normal_name = self
+            .plugin_directory
+            .join(library_filename("handmade_hero_plugin"));
+        let old_name = self
+            .plugin_directory
+            .join(library_filename("handmade_hero_plugin_old"));
+        let current_modified = std::fs::metadata(&normal_name)
+            .or_else(|_| std::fs::metadata(&old_name))
+            .and_then(|m| m.modified())
+            .map_err(|e| {
+                ApplicationError::wrap("Failed to get the application plugin file metadata", e)
+            })?;
+        if self
+            .last_modified
+            .is_some_and(|last_modified| last_modified >= current_modified)
+        {
+            return Ok(());
+        }
+
+        let running_name = self
+            .plugin_directory
+            .join(Self::running_name(self.running_count));
+        self.running_count += 1;
+        Self::copy_plugin_library(&normal_name, &running_name)?;
+        let stub = match Self::load_stub(&running_name) {
+            Ok(stub) => stub,
+            Err(error) => {
+                std::fs::remove_file(&running_name).unwrap_or_default(); // Okay to fail
+                if error.kind() == ErrorKind::IncompatiblePlugin {
+                    // Retrying won't help until the plugin is rebuilt.
+                    self.last_modified = Some(current_modified);
+                }
+                return Err(error);
+            }
+        };
+        stub.initialize(context)?;
+
+        // Only now is it safe to unload the previous plugin.
+        self.stub = Some(stub);
+        self.last_modified = Some(current_modified);
+        Ok(())
+    }
+
+    fn report(&mut self, error: &ApplicationError) {
+        let message = error.to_string();
+        if self.last_error.as_ref() != Some(&message) {
+            (self.error_handler)(error);
+            self.last_error = Some(message);
+        }
+    }
+
+    fn load_stub(running_name: &Path) -> Result<ApplicationStub> {
+        #[cfg(target_os = "linux")]
+        Self::verify_complete(running_name)?;
+        let library = unsafe { Library::new(running_name) }
+            .map_err(|e| ApplicationError::wrap("Could not load the application library", e))?;
+        Self::verify_abi(&library)?;
+        let creator: Symbol<'_, fn() -> Box<dyn Application>> = unsafe {
+            library.get(b"create_application")
+        }
+        .map_err(|e| ApplicationError::wrap("Could not load the application implementation", e))?;
+        let application = creator();
+        Ok(ApplicationStub {
+            application,
+            _library: library,
+        })
+    }
+
+    /// Opening a truncated shared object on Linux crashes the process with `SIGBUS`
+    /// rather than failing, so make sure the compiler has finished writing the file.
+    /// The section header table is written last, so the file is complete if it fits.
+    #[cfg(target_os = "linux")]
+    fn verify_complete(running_name: &Path) -> Result<()> {
+        const ELF_MAGIC: &[u8] = b"\x7fELF";
+        const CLASS_64: u8 = 2;
+        const LITTLE_ENDIAN: u8 = 1;
+
+        let contents = std::fs::read(running_name)
+            .map_err(|e| ApplicationError::wrap("Could not read the application library", e))?;
+        let incomplete = || ApplicationError::new("The application library is incomplete");
+        let header = contents.get(..0x40).ok_or_else(incomplete)?;
+        if &header[..4] != ELF_MAGIC || header[4] != CLASS_64 || header[5] != LITTLE_ENDIAN {
+            // Leave anything unexpected for the dynamic loader to reject.
+            return Ok(());
+        }
+        let read_u16 =
+            |offset: usize| u64::from(u16::from_le_bytes([header[offset], header[offset + 1]]));
+        let mut section_offset = [0u8; 8];
+        section_offset.copy_from_slice(&header[0x28..0x30]);
+        let section_offset = u64::from_le_bytes(section_offset);
+        let section_size = read_u16(0x3A) * read_u16(0x3C);
+        if section_offset.saturating_add(section_size) > contents.len() as u64 {
+            return Err(incomplete());
+        }
+        Ok(())
+    }
+
+    fn verify_abi(library: &Library) -> Result<()> {
+        let plugin_abi: Symbol<'_, extern "C" fn() -> PluginAbi> =
+            unsafe { library.get(b"plugin_abi") }.map_err(|e| {
+                ApplicationError::wrap("The application plugin does not describe its ABI", e)
+                    .with_kind(ErrorKind::IncompatiblePlugin)
+            })?;
+        plugin_abi().ensure_compatible_with(&PluginAbi::current())
+    }
+
+    fn running_name(counter: usize) -> OsString {
+        let running_name = format!("handmade_hero_plugin-running{counter}");
+        library_filename(running_name)
+    }
+
+    fn copy_plugin_library(normal_file: &Path, running_file: &Path) -> Result<()> {
+        std::fs::copy(normal_file, running_file)
+            .map_err(|e| {
+                // Don't leave a partial copy behind.
+                std::fs::remove_file(running_file).unwrap_or_default(); // Okay to fail
+                ApplicationError::wrap("Failed to copy the application plugin", e)
+            })
+            .map(|_| ())
+    }
+}
+
+impl Drop for ApplicationLoader {
+    fn drop(&mut self) {
+        self.stub = None;
+        for counter in 0..self.running_count {
+            let running_name = self.plugin_directory.join(Self::running_name(counter));
+            std::fs::remove_file(running_name).unwrap_or_default(); // Okay to fail
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod application_loader;
+pub mod playback_controller;
+pub mod playback_recorder;
+mod recording_file;
@@ -0,0 +1,225 @@
+use crate::playback_recorder::{PlaybackRecorder, PlaybackState};
+use handmade_hero_interface::application_error::Result;
+use handmade_hero_interface::platform_status::RecordingStatus;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Command {
+    StepForward,
+    StepBackward,
+    Seek(u64),
+}
+
+/// Adds pausing, single-stepping and seeking on top of a `PlaybackRecorder`.
+///
+/// Since recordings only store input between keyframes, reaching an arbitrary frame
+/// means restoring the closest keyframe before it and simulating every frame from
+/// there. Each host frame, `update` returns the recorded frames the host must simulate
+/// in order, the last of which is the one to display.
+#[derive(Debug, Default)]
+pub struct PlaybackController {
+    paused: bool,
+    command: Option<Command>,
+}
+
+impl PlaybackController {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    #[inline]
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    #[inline]
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    #[inline]
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Pauses and moves ahead a single frame.
+    #[inline]
+    pub fn step_forward(&mut self) {
+        self.paused = true;
+        self.command = Some(Command::StepForward);
+    }
+
+    /// Pauses and moves back a single frame.
+    #[inline]
+    pub fn step_backward(&mut self) {
+        self.paused = true;
+        self.command = Some(Command::StepBackward);
+    }
+
+    /// Jumps to the given frame, or the last frame if it is past the end. Playback
+    /// continues from there unless paused.
+    #[inline]
+    pub fn seek(&mut self, frame: u64) {
+        self.command = Some(Command::Seek(frame));
+    }
+
+    /// Describes what the recorder is doing, for showing on screen.
+    #[must_use]
+    pub fn status(&self, recorder: &PlaybackRecorder) -> RecordingStatus {
+        if let Some(slot) = recorder.recording_slot() {
+            RecordingStatus::Recording {
+                slot,
+                frame_count: recorder.frame_count(),
+            }
+        } else if let Some(position) = recorder.playback_position() {
+            RecordingStatus::Playing {
+                slot: recorder.playing_slot(),
+                position,
+                frame_count: recorder.frame_count(),
+                paused: self.paused,
+            }
+        } else {
+            RecordingStatus::Idle
+        }
+    }
+
+    /// Reads the frames the host must simulate to carry out the latest command, or the
+    /// next frame if playing. Nothing is returned while paused or if the recorder is not
+    /// playing anything. Reaching the end of the recording loops back to the start.
+    ///
+    /// # Errors
+    /// An error is returned if the recording cannot be read.
+    pub fn update(&mut self, recorder: &mut PlaybackRecorder) -> Result<Vec<PlaybackState>> {
+        let command = self.command.take();
+        let Some(position) = recorder.playback_position() else {
+            return Ok(Vec::new());
+        };
+        let frame_count = recorder.frame_count();
+        if frame_count == 0 {
+            return Ok(Vec::new());
+        }
+        let last_frame = frame_count - 1;
+        let target = match command {
+            Some(Command::StepForward) => position.min(last_frame),
+            Some(Command::StepBackward) => position.saturating_sub(2),
+            Some(Command::Seek(frame)) => frame.min(last_frame),
+            None if self.paused => return Ok(Vec::new()),
+            None if position == frame_count => 0,
+            None => position,
+        };
+
+        // Simulating forward is only avoidable by jumping to a later keyframe.
+        let keyframe = recorder.keyframe_before(target);
+        if target < position || keyframe > position {
+            recorder.seek(target)?;
+        }
+        let mut frames = Vec::new();
+        while recorder.playback_position().is_some_and(|p| p <= target) {
+            let Some(frame) = recorder.playback()? else {
+                break;
+            };
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback_controller::PlaybackController;
+    use crate::playback_recorder::PlaybackRecorder;
+    use handmade_hero_interface::game_state::GameState;
+    use handmade_hero_interface::input_state::InputState;
+    use handmade_hero_interface::platform_status::RecordingStatus;
+    use std::num::NonZeroU64;
+
+    /// Returns the mouse position of each frame, which doubles as its index, and
+    /// whether it carried a keyframe.
+    fn update(
+        controller: &mut PlaybackController,
+        recorder: &mut PlaybackRecorder,
+    ) -> Vec<(u32, bool)> {
+        controller
+            .update(recorder)
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.input.mouse().x(), f.state.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn test_pause_step_seek_and_reverse() {
+        let directory =
+            std::env::temp_dir().join(format!("handmade_hero_controller_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.set_keyframe_interval(NonZeroU64::new(3));
+        recorder.start_recording(0, 30f32).unwrap();
+        let state = GameState::new();
+        let mut input = InputState::new();
+        for index in 0..8 {
+            input.mouse_mut().set_x(index);
+            recorder.record(&input, &state).unwrap();
+        }
+        let mut controller = PlaybackController::new();
+        assert_eq!(
+            RecordingStatus::Recording {
+                slot: 0,
+                frame_count: 8
+            },
+            controller.status(&recorder)
+        );
+        recorder.start_playback(0).unwrap();
+
+        assert_eq!(vec![(0, true)], update(&mut controller, &mut recorder));
+        assert_eq!(vec![(1, false)], update(&mut controller, &mut recorder));
+
+        controller.pause();
+        assert!(update(&mut controller, &mut recorder).is_empty());
+
+        controller.step_forward();
+        assert_eq!(vec![(2, false)], update(&mut controller, &mut recorder));
+
+        controller.step_backward();
+        assert_eq!(
+            vec![(0, true), (1, false)],
+            update(&mut controller, &mut recorder)
+        );
+
+        controller.seek(7);
+        assert_eq!(
+            vec![(6, true), (7, false)],
+            update(&mut controller, &mut recorder)
+        );
+        assert!(controller.is_paused());
+        assert_eq!(
+            RecordingStatus::Playing {
+                slot: Some(0),
+                position: 8,
+                frame_count: 8,
+                paused: true
+            },
+            controller.status(&recorder)
+        );
+
+        // Stepping past the end stays on the last frame.
+        controller.step_forward();
+        assert_eq!(
+            vec![(6, true), (7, false)],
+            update(&mut controller, &mut recorder)
+        );
+
+        controller.resume();
+        assert_eq!(vec![(0, true)], update(&mut controller, &mut recorder));
+
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+use crate::recording_file::{RecordingHeader, frame_offsets, read_frame, write_frame};
+use bincode::{Decode, Encode};
+use handmade_hero_interface::application_error::{ApplicationError, ErrorKind, Result};
+use handmade_hero_interface::game_state::GameState;
+use handmade_hero_interface::input_state::InputState;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+enum State {
+    #[default]
+    None,
+    Recording {
+        slot: usize,
+        writer: BufWriter<File>,
+        header: RecordingHeader,
+    },
+    Playing {
+        slot: Option<usize>,
+        reader: BufReader<File>,
+        header: RecordingHeader,
+        frame_offsets: Vec<u64>,
+        position: u64,
+    },
+}
+
+/// The payload of a recorded frame: the frame's input and, on keyframes, the game
+/// state before the input was processed.
+#[derive(Debug, Encode)]
+struct PlaybackEncoding<'a>(
+    #[bincode(with_serde)] &'a InputState,
+    #[bincode(with_serde)] Option<&'a GameState>,
+);
+
+#[derive(Debug, Decode)]
+struct PlaybackDecoding(
+    #[bincode(with_serde)] InputState,
+    #[bincode(with_serde)] Option<GameState>,
+);
+
+#[derive(Debug)]
+pub struct PlaybackState {
+    pub input: InputState,
+    /// The game state to restore before processing `input`. This is only present on
+    /// keyframes; on every other frame the game is expected to re-simulate it.
+    pub state: Option<GameState>,
+}
+
+/// Records input to numbered slots on disk and plays it back. Each slot is its own
+/// `.hmr` file (see `recording_file`), so recordings survive between sessions.
+///
+/// Only the first frame of a recording stores the full game state. After that, just
+/// the input is stored and playback relies on the game re-simulating deterministically.
+/// Keyframes with a full snapshot can optionally be stored periodically as well, so
+/// playback can start somewhere other than the beginning.
+#[derive(Debug, Default)]
+pub struct PlaybackRecorder {
+    recording_directory: PathBuf,
+    keyframe_interval: Option<NonZeroU64>,
+    state: State,
+}
+
+impl PlaybackRecorder {
+    pub const SLOT_COUNT: usize = 4;
+
+    #[inline]
+    #[must_use]
+    pub fn new(recording_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            recording_directory: recording_directory.into(),
+            keyframe_interval: None,
+            state: State::None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn keyframe_interval(&self) -> Option<NonZeroU64> {
+        self.keyframe_interval
+    }
+
+    /// Sets how many frames apart new recordings store a full game state snapshot.
+    /// With `None`, only the first frame does.
+    #[inline]
+    pub fn set_keyframe_interval(&mut self, interval: Option<NonZeroU64>) {
+        self.keyframe_interval = interval;
+    }
+
+    /// The path of the recording file backing the given slot.
+    #[must_use]
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.recording_directory
+            .join(format!("recording{slot}.hmr"))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn recording_slot(&self) -> Option<usize> {
+        match self.state {
+            State::Recording { slot, .. } => Some(slot),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn playing_slot(&self) -> Option<usize> {
+        match self.state {
+            State::Playing { slot, .. } => slot,
+            _ => None,
+        }
+    }
+
+    /// The number of frames in the current recording or playback.
+    #[inline]
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.header().map_or(0, |h| h.frame_count)
+    }
+
+    /// The frame rate of the current recording or playback.
+    #[inline]
+    #[must_use]
+    pub fn frames_per_second(&self) -> Option<f32> {
+        self.header().map(|h| h.frames_per_second)
+    }
+
+    /// How many frames apart the current recording or playback stores keyframes.
+    #[inline]
+    #[must_use]
+    pub fn recorded_keyframe_interval(&self) -> Option<NonZeroU64> {
+        self.header().and_then(|h| h.keyframe_interval)
+    }
+
+    /// The index of the next frame `playback` will return.
+    #[inline]
+    #[must_use]
+    pub fn playback_position(&self) -> Option<u64> {
+        match self.state {
+            State::Playing { position, .. } => Some(position),
+            _ => None,
+        }
+    }
+
+    /// The closest keyframe at or before the given frame of the current recording or
+    /// playback.
+    #[inline]
+    #[must_use]
+    pub fn keyframe_before(&self, frame: u64) -> u64 {
+        self.header().map_or(0, |h| h.keyframe_before(frame))
+    }
+
+    fn header(&self) -> Option<&RecordingHeader> {
+        match self.state {
+            State::None => None,
+            State::Recording { ref header, .. } | State::Playing { ref header, .. } => Some(header),
+        }
+    }
+
+    /// Starts a new recording in the given slot, replacing whatever it held before.
+    ///
+    /// # Errors
+    /// An error is returned if the slot is out of range or the file cannot be created.
+    pub fn start_recording(&mut self, slot: usize, frames_per_second: f32) -> Result<()> {
+        Self::check_slot(slot)?;
+        self.stop()?;
+        let file = File::options()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(self.slot_path(slot))
+            .map_err(|e| ApplicationError::wrap("Could not create the recording file", e))?;
+        let mut writer = BufWriter::new(file);
+        let header = RecordingHeader {
+            frames_per_second,
+            frame_count: 0,
+            keyframe_interval: self.keyframe_interval,
+        };
+        header
+            .write(&mut writer)
+            .map_err(|e| ApplicationError::wrap("Could not write the recording header", e))?;
+        self.state = State::Recording {
+            slot,
+            writer,
+            header,
+        };
+        Ok(())
+    }
+
+    /// Appends a frame to the current recording. Does nothing if not recording. The
+    /// game state is only written if this frame is a keyframe, so it should be passed
+    /// as it was before the input is processed.
+    ///
+    /// # Errors
+    /// An error is returned if the frame cannot be written.
+    pub fn record(&mut self, input: &InputState, state: &GameState) -> Result<()> {
+        let State::Recording {
+            ref mut writer,
+            ref mut header,
+            ..
+        } = self.state
+        else {
+            return Ok(());
+        };
+        let is_keyframe = header.is_keyframe(header.frame_count);
+        let recording = PlaybackEncoding(input, is_keyframe.then_some(state));
+        let payload = bincode::encode_to_vec(recording, bincode::config::standard())
+            .map_err(|e| ApplicationError::wrap("Could not encode the recorded state", e))?;
+        write_frame(writer, &payload).map_err(|e| {
+            ApplicationError::wrap("Could not write the state to the recording file", e)
+        })?;
+        header.frame_count += 1;
+        Ok(())
+    }
+
+    /// Stops recording or playing. A recording's header is updated with its final
+    /// frame count.
+    ///
+    /// # Errors
+    /// An error is returned if a recording cannot be finalized.
+    pub fn stop(&mut self) -> Result<()> {
+        if let State::Recording { writer, header, .. } = std::mem::take(&mut self.state) {
+            Self::finish_recording(writer, header.frame_count)
+                .map_err(|e| ApplicationError::wrap("Could not finalize the recording file", e))?;
+        }
+        Ok(())
+    }
+
+    fn finish_recording(writer: BufWriter<File>, frame_count: u64) -> std::io::Result<()> {
+        let mut file = writer
+            .into_inner()
+            .map_err(std::io::IntoInnerError::into_error)?;
+        file.seek(SeekFrom::Start(RecordingHeader::FRAME_COUNT_OFFSET))?;
+        file.write_all(&frame_count.to_le_bytes())?;
+        file.flush()
+    }
+
+    /// Starts playing the recording in the given slot from the beginning. If that slot
+    /// is currently being recorded, the recording is finished first.
+    ///
+    /// # Errors
+    /// An error is returned if the slot has no readable recording. If the file exists
+    /// but was made by an incompatible version of the game, the error's kind is
+    /// `ErrorKind::IncompatibleRecording`.
+    pub fn start_playback(&mut self, slot: usize) -> Result<()> {
+        Self::check_slot(slot)?;
+        self.start_playback_at(Some(slot), &self.slot_path(slot))
+    }
+
+    /// Starts playing a recording file from outside the recording directory, such as
+    /// one saved off from an earlier play session.
+    ///
+    /// # Errors
+    /// An error is returned if the file is not a readable recording. If it was made by
+    /// an incompatible version of the game, the error's kind is
+    /// `ErrorKind::IncompatibleRecording`.
+    pub fn start_playback_file(&mut self, path: &Path) -> Result<()> {
+        self.start_playback_at(None, path)
+    }
+
+    fn start_playback_at(&mut self, slot: Option<usize>, path: &Path) -> Result<()> {
+        self.stop()?;
+        let (reader, header, frame_offsets) = Self::open_recording(path)?;
+        self.state = State::Playing {
+            slot,
+            reader,
+            header,
+            frame_offsets,
+            position: 0,
+        };
+        Ok(())
+    }
+
+    fn open_recording(path: &Path) -> Result<(BufReader<File>, RecordingHeader, Vec<u64>)> {
+        let file = File::open(path)
+            .map_err(|e| ApplicationError::wrap("Could not open the recording file", e))?;
+        let mut reader = BufReader::new(file);
+        let mut header = RecordingHeader::read(&mut reader)?;
+        let frame_offsets = frame_offsets(&mut reader, &mut header)?;
+        Ok((reader, header, frame_offsets))
+    }
+
+    /// Reads the next frame of the current playback. Returns `None` when not playing
+    /// or once every frame has been played. The first frame always carries the game
+    /// state the recording started from.
+    ///
+    /// # Errors
+    /// An `ErrorKind::CorruptRecording` error is returned if a frame is truncated,
+    /// fails its checksum or cannot be decoded.
+    pub fn playback(&mut self) -> Result<Option<PlaybackState>> {
+        let State::Playing {
+            ref mut reader,
+            ref header,
+            ref mut position,
+            ..
+        } = self.state
+        else {
+            return Ok(None);
+        };
+        if *position == header.frame_count {
+            // Avoid trying to read past the last frame.
+            return Ok(None);
+        }
+        let mut payload = Vec::new();
+        read_frame(reader, &mut payload)?;
+        let (PlaybackDecoding(input, state), _) =
+            bincode::decode_from_slice(&payload, bincode::config::standard()).map_err(|e| {
+                ApplicationError::wrap("Could not decode the recorded state", e)
+                    .with_kind(ErrorKind::CorruptRecording)
+            })?;
+        *position += 1;
+        Ok(Some(PlaybackState { input, state }))
+    }
+
+    /// Moves the current playback to the closest keyframe at or before the given frame
+    /// and returns the keyframe's index. To show the requested frame itself, play and
+    /// simulate every frame from the keyframe up to and including it.
+    ///
+    /// # Errors
+    /// An error is returned if not playing, if the frame is past the end of the
+    /// recording, or if the file cannot be read.
+    pub fn seek(&mut self, frame: u64) -> Result<u64> {
+        let State::Playing {
+            ref mut reader,
+            ref header,
+            ref frame_offsets,
+            ref mut position,
+            ..
+        } = self.state
+        else {
+            return Err(ApplicationError::new("No recording is being played"));
+        };
+        if frame >= header.frame_count {
+            return Err(ApplicationError::new(format!(
+                "Frame {frame} is past the end of the recording ({} frames)",
+                header.frame_count
+            )));
+        }
+        let keyframe = header.keyframe_before(frame);
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = frame_offsets[keyframe as usize];
+        reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| ApplicationError::wrap("Could not seek in the recording file", e))?;
+        *position = keyframe;
+        Ok(keyframe)
+    }
+
+    /// Restarts the current playback from its first frame.
+    ///
+    /// # Errors
+    /// An error is returned if the recording can no longer be read.
+    pub fn reset_playback(&mut self) -> Result<()> {
+        if self.playback_position().is_some() && self.frame_count() > 0 {
+            self.seek(0)?;
+        }
+        Ok(())
+    }
+
+    fn check_slot(slot: usize) -> Result<()> {
+        if slot >= Self::SLOT_COUNT {
+            return Err(ApplicationError::new(format!(
+                "Recording slot {slot} is out of range"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PlaybackRecorder {
+    fn drop(&mut self) {
+        self.stop().unwrap_or_default(); // Nothing more we can do
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback_recorder::PlaybackRecorder;
+    use handmade_hero_interface::game_state::GameState;
+    use handmade_hero_interface::input_state::InputState;
+    use std::num::NonZeroU64;
+    use std::path::PathBuf;
+
+    fn recording_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("handmade_hero_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn record_frames(recorder: &mut PlaybackRecorder, frame_count: usize) {
+        let state = GameState::new();
+        let mut input = InputState::new();
+        for index in 0..frame_count {
+            input.mouse_mut().set_x(u32::try_from(index).unwrap());
+            recorder.record(&input, &state).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_playback_in_a_later_session() {
+        let directory = recording_directory("later_session");
+        {
+            let mut recorder = PlaybackRecorder::new(&directory);
+            recorder.start_recording(2, 30f32).unwrap();
+            record_frames(&mut recorder, 3);
+            // Dropping the recorder finalizes the recording.
+        }
+
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.start_playback(2).unwrap();
+        assert_eq!(3, recorder.frame_count());
+        assert_eq!(Some(30f32), recorder.frames_per_second());
+        for index in 0..3 {
+            let frame = recorder.playback().unwrap().unwrap();
+            assert_eq!(index, frame.input.mouse().x());
+        }
+        assert!(recorder.playback().unwrap().is_none());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_only_keyframes_store_state() {
+        let directory = recording_directory("keyframes");
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.start_recording(0, 30f32).unwrap();
+        record_frames(&mut recorder, 5);
+        recorder.set_keyframe_interval(NonZeroU64::new(2));
+        recorder.start_recording(1, 30f32).unwrap();
+        record_frames(&mut recorder, 5);
+
+        let mut keyframes = |slot| {
+            recorder.start_playback(slot).unwrap();
+            let mut keyframes = Vec::new();
+            while let Some(frame) = recorder.playback().unwrap() {
+                if frame.state.is_some() {
+                    keyframes.push(frame.input.mouse().x());
+                }
+            }
+            keyframes
+        };
+        assert_eq!(vec![0], keyframes(0));
+        assert_eq!(vec![0, 2, 4], keyframes(1));
+
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_slots_are_independent() {
+        let directory = recording_directory("independent_slots");
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.start_recording(0, 30f32).unwrap();
+        record_frames(&mut recorder, 2);
+        recorder.start_recording(1, 30f32).unwrap();
+        record_frames(&mut recorder, 5);
+
+        recorder.start_playback(0).unwrap();
+        assert_eq!(Some(0), recorder.playing_slot());
+        assert_eq!(2, recorder.frame_count());
+        recorder.start_playback(1).unwrap();
+        assert_eq!(5, recorder.frame_count());
+
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_random_numbers_replay_the_same() {
+        let directory = recording_directory("random_numbers");
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.start_recording(0, 30f32).unwrap();
+        let mut state = GameState::new();
+        state.random_mut().next_u32();
+        recorder.record(&InputState::new(), &state).unwrap();
+
+        recorder.start_playback(0).unwrap();
+        let mut replayed = recorder.playback().unwrap().unwrap().state.unwrap();
+        for _ in 0..8 {
+            assert_eq!(
+                state.random_mut().next_u32(),
+                replayed.random_mut().next_u32()
+            );
+        }
+
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_empty_slot_cannot_be_played() {
+        let directory = recording_directory("empty_slot");
+        let mut recorder = PlaybackRecorder::new(&directory);
+        assert!(recorder.start_playback(3).is_err());
+        assert!(
+            recorder
+                .start_recording(PlaybackRecorder::SLOT_COUNT, 30f32)
+                .is_err()
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
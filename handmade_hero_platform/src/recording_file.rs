@@ -0,0 +1,373 @@
+//! The `.hmr` recording container.
+//!
+//! ```text
+//! magic                  4 bytes  "HMR\0"
+//! format version         u32
+//! interface version      u32      `plugin_abi::INTERFACE_VERSION`
+//! game state layout hash u64      `PluginAbi::game_state_layout_hash`
+//! frames per second      f32
+//! frame count            u64      patched in once recording stops, 0 until then
+//! keyframe interval      u64      0 if only the first frame is a keyframe
+//! frames...              u32 payload length, payload, u32 CRC-32 of the payload
+//! ```
+//!
+//! All integers are little-endian.
+
+use handmade_hero_interface::application_error::{ApplicationError, ErrorKind, Result};
+use handmade_hero_interface::plugin_abi::{INTERFACE_VERSION, PluginAbi};
+use std::io::{ErrorKind as IoErrorKind, Read, Seek, SeekFrom, Write};
+use std::num::NonZeroU64;
+
+const MAGIC: [u8; 4] = *b"HMR\0";
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct RecordingHeader {
+    pub frames_per_second: f32,
+    pub frame_count: u64,
+    pub keyframe_interval: Option<NonZeroU64>,
+}
+
+impl RecordingHeader {
+    pub(crate) const FORMAT_VERSION: u32 = 4;
+    pub(crate) const FRAME_COUNT_OFFSET: u64 = 24;
+
+    pub(crate) fn write(self, writer: &mut impl Write) -> std::io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&Self::FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&INTERFACE_VERSION.to_le_bytes())?;
+        writer.write_all(&PluginAbi::current().game_state_layout_hash().to_le_bytes())?;
+        writer.write_all(&self.frames_per_second.to_le_bytes())?;
+        writer.write_all(&self.frame_count.to_le_bytes())?;
+        let keyframe_interval = self.keyframe_interval.map_or(0, NonZeroU64::get);
+        writer.write_all(&keyframe_interval.to_le_bytes())
+    }
+
+    /// Whether the given frame stores a full game state snapshot.
+    pub(crate) fn is_keyframe(&self, frame: u64) -> bool {
+        match self.keyframe_interval {
+            _ if frame == 0 => true,
+            Some(interval) => frame % interval == 0,
+            None => false,
+        }
+    }
+
+    /// The closest keyframe at or before the given frame.
+    pub(crate) fn keyframe_before(&self, frame: u64) -> u64 {
+        self.keyframe_interval
+            .map_or(0, |interval| frame - frame % interval)
+    }
+
+    /// # Errors
+    /// An `ErrorKind::IncompatibleRecording` error is returned if the file is not a
+    /// recording or was made by a different version of the game.
+    pub(crate) fn read(reader: &mut impl Read) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        read_exact(reader, &mut magic)?;
+        if magic != MAGIC {
+            return Err(incompatible("The file is not a recording"));
+        }
+        let format_version = u32::from_le_bytes(read_array(reader)?);
+        if format_version != Self::FORMAT_VERSION {
+            return Err(incompatible(format!(
+                "The recording format version {format_version} is not supported (expected {})",
+                Self::FORMAT_VERSION
+            )));
+        }
+        let interface_version = u32::from_le_bytes(read_array(reader)?);
+        if interface_version != INTERFACE_VERSION {
+            return Err(incompatible(format!(
+                "The recording was made with interface version {interface_version} (expected {INTERFACE_VERSION})"
+            )));
+        }
+        let layout_hash = u64::from_le_bytes(read_array(reader)?);
+        if layout_hash != PluginAbi::current().game_state_layout_hash() {
+            return Err(incompatible(
+                "The recording was made with a different game state layout",
+            ));
+        }
+        let frames_per_second = f32::from_le_bytes(read_array(reader)?);
+        let frame_count = u64::from_le_bytes(read_array(reader)?);
+        let keyframe_interval = NonZeroU64::new(u64::from_le_bytes(read_array(reader)?));
+        Ok(Self {
+            frames_per_second,
+            frame_count,
+            keyframe_interval,
+        })
+    }
+}
+
+pub(crate) fn write_frame(writer: &mut impl Write, payload: &[u8]) -> std::io::Result<()> {
+    let length = u32::try_from(payload.len())
+        .map_err(|_| std::io::Error::new(IoErrorKind::InvalidInput, "The frame is too large"))?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&crc32(payload).to_le_bytes())
+}
+
+/// Finds where each of the header's frames begins, starting from the reader's current
+/// position, which is restored afterward. Only the length prefixes are read.
+///
+/// A header with no frames may belong to a recording that was never stopped (e.g., the
+/// game crashed), so its frames are counted instead, ignoring a partly written last
+/// frame, and the header is updated with the count.
+///
+/// # Errors
+/// An `ErrorKind::CorruptRecording` error is returned if the file ends before the
+/// last frame does.
+pub(crate) fn frame_offsets(
+    reader: &mut (impl Read + Seek),
+    header: &mut RecordingHeader,
+) -> Result<Vec<u64>> {
+    let seek = |reader: &mut _, position| {
+        Seek::seek(reader, position)
+            .map_err(|e| ApplicationError::wrap("Could not read the recording file", e))
+    };
+    let start = seek(reader, SeekFrom::Current(0))?;
+    let end = seek(reader, SeekFrom::End(0))?;
+    let mut offsets = Vec::new();
+    let mut offset = start;
+    if header.frame_count == 0 {
+        while offset + 4 <= end {
+            seek(reader, SeekFrom::Start(offset))?;
+            let length = u32::from_le_bytes(read_array(reader)?);
+            let next = offset + 4 + u64::from(length) + 4;
+            if next > end {
+                break;
+            }
+            offsets.push(offset);
+            offset = next;
+        }
+        header.frame_count = offsets.len() as u64;
+        seek(reader, SeekFrom::Start(start))?;
+        return Ok(offsets);
+    }
+    for _ in 0..header.frame_count {
+        seek(reader, SeekFrom::Start(offset))?;
+        let length = u32::from_le_bytes(read_array(reader)?);
+        offsets.push(offset);
+        offset += 4 + u64::from(length) + 4;
+        if offset > end {
+            return Err(
+                ApplicationError::new("The last recorded frame is truncated")
+                    .with_kind(ErrorKind::CorruptRecording),
+            );
+        }
+    }
+    seek(reader, SeekFrom::Start(start))?;
+    Ok(offsets)
+}
+
+/// Reads the next frame's payload into the buffer, replacing its contents.
+///
+/// # Errors
+/// An `ErrorKind::CorruptRecording` error is returned if the frame is truncated or
+/// fails its checksum.
+pub(crate) fn read_frame(reader: &mut impl Read, payload: &mut Vec<u8>) -> Result<()> {
+    let length = u32::from_le_bytes(read_array(reader)?);
+    // Don't trust the length enough to allocate it up front; only read what is there.
+    payload.clear();
+    reader
+        .take(u64::from(length))
+        .read_to_end(payload)
+        .map_err(|e| ApplicationError::wrap("Could not read the recording file", e))?;
+    if payload.len() != length as usize {
+        return Err(ApplicationError::new("A recorded frame is truncated")
+            .with_kind(ErrorKind::CorruptRecording));
+    }
+    let checksum = u32::from_le_bytes(read_array(reader)?);
+    if checksum != crc32(payload) {
+        return Err(
+            ApplicationError::new("A recorded frame failed its checksum")
+                .with_kind(ErrorKind::CorruptRecording),
+        );
+    }
+    Ok(())
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+    let mut buffer = [0u8; N];
+    read_exact(reader, &mut buffer)?;
+    Ok(buffer)
+}
+
+fn read_exact(reader: &mut impl Read, buffer: &mut [u8]) -> Result<()> {
+    reader.read_exact(buffer).map_err(|e| {
+        let kind = if e.kind() == IoErrorKind::UnexpectedEof {
+            ErrorKind::CorruptRecording
+        } else {
+            ErrorKind::Other
+        };
+        ApplicationError::wrap("Could not read the recording file", e).with_kind(kind)
+    })
+}
+
+fn incompatible(message: impl Into<String>) -> ApplicationError {
+    ApplicationError::new(message).with_kind(ErrorKind::IncompatibleRecording)
+}
+
+/// The CRC-32 (IEEE) checksum, as used by zip and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut index = 0;
+        while index < 256 {
+            #[allow(clippy::cast_possible_truncation)]
+            let mut value = index as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                value = if value & 1 == 1 {
+                    (value >> 1) ^ 0xEDB8_8320
+                } else {
+                    value >> 1
+                };
+                bit += 1;
+            }
+            table[index] = value;
+            index += 1;
+        }
+        table
+    };
+
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::recording_file::{RecordingHeader, crc32, frame_offsets, read_frame, write_frame};
+    use handmade_hero_interface::application_error::ErrorKind;
+    use std::io::Cursor;
+    use std::num::NonZeroU64;
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = RecordingHeader {
+            frames_per_second: 30f32,
+            frame_count: 42,
+            keyframe_interval: NonZeroU64::new(90),
+        };
+        let mut bytes = Vec::new();
+        header.write(&mut bytes).unwrap();
+        assert_eq!(RecordingHeader::FRAME_COUNT_OFFSET + 16, bytes.len() as u64);
+        let read = RecordingHeader::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header, read);
+    }
+
+    #[test]
+    fn test_header_rejects_other_files() {
+        let error = RecordingHeader::read(&mut b"PNG\0 and then some".as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::IncompatibleRecording, error.kind());
+    }
+
+    #[test]
+    fn test_header_rejects_other_versions() {
+        let header = RecordingHeader {
+            frames_per_second: 30f32,
+            frame_count: 0,
+            keyframe_interval: None,
+        };
+        let mut bytes = Vec::new();
+        header.write(&mut bytes).unwrap();
+        bytes[4] = 1; // Format version 1
+        let error = RecordingHeader::read(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(ErrorKind::IncompatibleRecording, error.kind());
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"first").unwrap();
+        write_frame(&mut bytes, b"second").unwrap();
+        let mut reader = bytes.as_slice();
+        let mut payload = Vec::new();
+        read_frame(&mut reader, &mut payload).unwrap();
+        assert_eq!(b"first", payload.as_slice());
+        read_frame(&mut reader, &mut payload).unwrap();
+        assert_eq!(b"second", payload.as_slice());
+    }
+
+    #[test]
+    fn test_keyframe_before() {
+        let mut header = RecordingHeader {
+            frames_per_second: 30f32,
+            frame_count: 100,
+            keyframe_interval: None,
+        };
+        assert_eq!(0, header.keyframe_before(75));
+        header.keyframe_interval = NonZeroU64::new(30);
+        assert_eq!(60, header.keyframe_before(75));
+        assert_eq!(60, header.keyframe_before(60));
+        assert!(header.is_keyframe(0));
+        assert!(!header.is_keyframe(75));
+    }
+
+    #[test]
+    fn test_frame_offsets() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"first").unwrap();
+        write_frame(&mut bytes, b"second").unwrap();
+        let mut header = RecordingHeader {
+            frames_per_second: 30f32,
+            frame_count: 2,
+            keyframe_interval: None,
+        };
+        let offsets = frame_offsets(&mut Cursor::new(&bytes), &mut header).unwrap();
+        assert_eq!(vec![0, 13], offsets);
+
+        header.frame_count = 3;
+        let error = frame_offsets(&mut Cursor::new(&bytes), &mut header).unwrap_err();
+        assert_eq!(ErrorKind::CorruptRecording, error.kind());
+    }
+
+    #[test]
+    fn test_frame_offsets_of_unfinished_recording() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"first").unwrap();
+        write_frame(&mut bytes, b"second").unwrap();
+        write_frame(&mut bytes, b"third").unwrap();
+        bytes.truncate(bytes.len() - 3); // Crashed while writing the last frame
+        let mut header = RecordingHeader {
+            frames_per_second: 30f32,
+            frame_count: 0,
+            keyframe_interval: None,
+        };
+        let offsets = frame_offsets(&mut Cursor::new(&bytes), &mut header).unwrap();
+        assert_eq!(vec![0, 13], offsets);
+        assert_eq!(2, header.frame_count);
+    }
+
+    #[test]
+    fn test_frame_detects_corruption() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"payload").unwrap();
+        bytes[6] ^= 0xFF;
+        let error = read_frame(&mut bytes.as_slice(), &mut Vec::new()).unwrap_err();
+        assert_eq!(ErrorKind::CorruptRecording, error.kind());
+    }
+
+    #[test]
+    fn test_frame_rejects_oversized_length() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"payload").unwrap();
+        bytes[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = read_frame(&mut bytes.as_slice(), &mut Vec::new()).unwrap_err();
+        assert_eq!(ErrorKind::CorruptRecording, error.kind());
+    }
+
+    #[test]
+    fn test_frame_detects_truncation() {
+        let mut bytes = Vec::new();
+        write_frame(&mut bytes, b"payload").unwrap();
+        bytes.truncate(bytes.len() - 2);
+        let error = read_frame(&mut bytes.as_slice(), &mut Vec::new()).unwrap_err();
+        assert_eq!(ErrorKind::CorruptRecording, error.kind());
+    }
+}
@@ -1,38 +1,63 @@
+use crate::player_sprites::PlayerSprites;
 use handmade_hero_interface::application::Application;
 use handmade_hero_interface::application_error::Result;
 use handmade_hero_interface::audio_context::AudioContext;
+use handmade_hero_interface::bitmap_font::{BitmapFont, TextStyle};
 use handmade_hero_interface::button_state::ButtonState;
-use handmade_hero_interface::color::Color;
+use handmade_hero_interface::color::{BlendMode, Color};
 use handmade_hero_interface::controller_state::ControllerState;
+use handmade_hero_interface::direction::Direction;
 use handmade_hero_interface::game_state::GameState;
 use handmade_hero_interface::initialize_context::InitializeContext;
 use handmade_hero_interface::input_context::InputContext;
 use handmade_hero_interface::input_state::InputState;
+use handmade_hero_interface::level::Level;
+use handmade_hero_interface::platform_status::{PlatformStatus, RecordingStatus};
+use handmade_hero_interface::player::Player;
+use handmade_hero_interface::plugin_abi::PluginAbi;
 use handmade_hero_interface::point_2d::Point2d;
+use handmade_hero_interface::rasterizer::Rasterizer;
 use handmade_hero_interface::rectangle::Rectangle;
 use handmade_hero_interface::render_context::RenderContext;
-use handmade_hero_interface::tile_map::TileMap;
-use handmade_hero_interface::tile_map_coordinate::TileMapCoordinate;
-use handmade_hero_interface::tile_map_key::TileMapKey;
+use handmade_hero_interface::render_target::RenderTarget;
+use handmade_hero_interface::tile_coordinate::TileCoordinate;
+use handmade_hero_interface::tile_definition::TileDefinition;
+use handmade_hero_interface::tiled::TiledMap;
 use handmade_hero_interface::units::si::length::{Length, pixel};
 use handmade_hero_interface::units::si::time::Time;
 use handmade_hero_interface::world::World;
 use handmade_hero_interface::world_coordinate::WorldCoordinate;
-use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::sync::OnceLock;
 use uom::si::length::meter;
-use uom::si::time::second;
-
-#[derive(Debug)]
-pub struct ApplicationPlugin {}
+use uom::si::time::{millisecond, second};
+
+/// The name of the level file looked for next to the executable.
+const LEVEL_FILE_NAME: &str = "world.level";
+/// The name of the Tiled map looked for next to the executable, used instead of the level
+/// file when there is one.
+const TILED_FILE_NAME: &str = "world.json";
+const DEFAULT_LEVEL: &str = include_str!("../levels/world.level");
+
+#[derive(Debug, Default)]
+pub struct ApplicationPlugin {
+    player_sprites: OnceLock<Option<PlayerSprites>>,
+}
 
 impl ApplicationPlugin {
     #[unsafe(no_mangle)]
     #[must_use]
     pub extern "Rust" fn create_application() -> Box<dyn Application> {
-        Box::new(Self {})
+        Box::new(Self::default())
     }
 
-    fn initialize_direct(state: &mut GameState) {
+    #[unsafe(no_mangle)]
+    #[must_use]
+    pub extern "C" fn plugin_abi() -> PluginAbi {
+        PluginAbi::current()
+    }
+
+    fn initialize_direct(state: &mut GameState) -> Result<()> {
         // Put the player somewhere in the middle
         let width = state.width().get::<pixel>();
         let height = state.height().get::<pixel>();
@@ -46,156 +71,115 @@ impl ApplicationPlugin {
             state.player().tile_map_key(),
             tile_map_coordinates,
         );
-        state.player_mut().set_coordinates(new_coordinates);
+        state.player_mut().set_coordinates(new_coordinates.clone());
 
         // Load the world tile maps
-        let world = state.world_mut();
-        let hub = world.add_tile_map(TileMapKey { x: 0, y: 0 }); // Origin
-        Self::load_hub_tile_map(hub);
-        let south = world.add_tile_map(TileMapKey { x: 0, y: -1 });
-        Self::load_south_tile_map(south);
-        let west = world.add_tile_map(TileMapKey { x: -1, y: 0 });
-        Self::load_west_tile_map(west);
-        let east = world.add_tile_map(TileMapKey { x: 1, y: 0 });
-        Self::load_east_tile_map(east);
-        let north = world.add_tile_map(TileMapKey { x: 0, y: 1 });
-        Self::load_north_tile_map(north);
-    }
-
-    fn load_south_tile_map(south: &mut TileMap) {
-        let source_south: [[u32; World::TILE_COLUMNS]; World::TILE_ROWS] = [
-            [1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 1, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-        ];
-        Self::load_tile_map(
-            south,
-            source_south.as_flattened(),
-            World::TILE_ROWS,
-            World::TILE_COLUMNS,
-        );
-    }
+        Self::load_world(state.world_mut())?;
 
-    fn load_hub_tile_map(hub: &mut TileMap) {
-        let source_hub: [[u32; World::TILE_COLUMNS]; World::TILE_ROWS] = [
-            [1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1],
-        ];
-        Self::load_tile_map(
-            hub,
-            source_hub.as_flattened(),
-            World::TILE_ROWS,
-            World::TILE_COLUMNS,
-        );
+        // Start the camera on the player, keeping it over the tile maps
+        let bounds = state.world().bounds(new_coordinates.tile().z());
+        let camera = state.camera_mut();
+        camera.set_bounds(bounds);
+        camera.snap_to(&new_coordinates, width, height);
+        Ok(())
     }
 
-    fn load_west_tile_map(west: &mut TileMap) {
-        let source_west: [[u32; World::TILE_COLUMNS]; World::TILE_ROWS] = [
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [1, 0, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-        ];
-        Self::load_tile_map(
-            west,
-            source_west.as_flattened(),
-            World::TILE_ROWS,
-            World::TILE_COLUMNS,
-        );
+    /// Populates the world from the Tiled map or level next to the executable, so
+    /// designers can change it without rebuilding the plugin, or the built-in level if
+    /// there is neither.
+    ///
+    /// # Errors
+    /// An error is returned if there is a map or level next to the executable that
+    /// cannot be loaded, rather than quietly playing a different world.
+    fn load_world(world: &mut World) -> Result<()> {
+        if let Some(path) = Self::find_next_to_executable(TILED_FILE_NAME) {
+            TiledMap::load(&path)?.populate(world);
+        } else if let Some(path) = Self::find_next_to_executable(LEVEL_FILE_NAME) {
+            Level::load(&path)?.populate(world);
+        } else {
+            Level::parse(DEFAULT_LEVEL)
+                .expect("The built-in level is malformed")
+                .populate(world);
+        }
+        Ok(())
     }
 
-    fn load_east_tile_map(east: &mut TileMap) {
-        let source_east: [[u32; World::TILE_COLUMNS]; World::TILE_ROWS] = [
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0, 1],
-            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-        ];
-        Self::load_tile_map(
-            east,
-            source_east.as_flattened(),
-            World::TILE_ROWS,
-            World::TILE_COLUMNS,
-        );
+    fn find_next_to_executable(file_name: &str) -> Option<PathBuf> {
+        std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|d| d.join(file_name)))
+            .filter(|p| p.exists())
     }
 
-    fn load_north_tile_map(north: &mut TileMap) {
-        let source_north: [[u32; World::TILE_COLUMNS]; World::TILE_ROWS] = [
-            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 1, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1],
-        ];
-        Self::load_tile_map(
-            north,
-            source_north.as_flattened(),
-            World::TILE_ROWS,
-            World::TILE_COLUMNS,
-        );
+    fn process_input_direct(input: &InputState, state: &mut GameState) {
+        Self::move_player(input, state);
+        Self::update_camera(state);
     }
 
-    fn load_tile_map(
-        destination: &mut TileMap,
-        source: &[u32],
-        row_count: usize,
-        column_count: usize,
-    ) {
-        // In our world coordinate system, the y-coordinates increase from the bottom up.
-        // But in memory, the y-coordinates increase from the top down. Therefore, when
-        // we copy our hard-coded tile map arrays, we flip the coordinate of each row.
-        let mut index = 0;
-        for row_index in 0..row_count {
-            for column_index in 0..column_count {
-                let value = source[index];
-                let destination_row_index = row_count - row_index - 1;
-                destination[(destination_row_index, column_index)] = value;
-                index += 1;
-            }
-        }
+    fn move_player(input: &InputState, state: &mut GameState) {
+        let (delta_x, delta_y) = Self::calculate_delta_x_y(input, state);
+        let Some(direction) = Direction::from_delta(delta_x, delta_y) else {
+            state.player_mut().stand();
+            return;
+        };
+        // Keep walking in place when blocked, so the player can see which way they face.
+        let frame_duration = state.frame_duration();
+        state.player_mut().walk(direction, frame_duration);
+
+        // Slide along walls rather than stopping dead, without passing through them.
+        let old_tile = Self::determine_standing_tile(state.player());
+        let new_coordinates = state.world().move_and_slide(
+            state.player().coordinate(),
+            state.player().collision_bounds(),
+            Point2d::from_x_y(delta_x, delta_y),
+        );
+        state.player_mut().set_coordinates(new_coordinates);
+        Self::climb_stairs(state, old_tile);
     }
 
-    fn process_input_direct(input: &InputState, state: &mut GameState) {
-        let (delta_x, delta_y) = Self::calculate_delta_x_y(input, state);
-        if delta_x == 0f32 && delta_y == 0f32 {
+    /// Moves the player to another level when they step onto stairs. Only stepping onto
+    /// stairs counts, so arriving on the stairs at the other end does not send the player
+    /// straight back.
+    fn climb_stairs(state: &mut GameState, old_tile: TileCoordinate) {
+        let tile = Self::determine_standing_tile(state.player());
+        if tile == old_tile {
             return;
         }
-
-        let world = state.world();
-        let old_coordinates = state.player().coordinate();
-        let new_coordinates = old_coordinates.shifted(delta_x, delta_y);
-        if !world.is_traversable(&new_coordinates, state.player().collision_bounds()) {
+        let Some(level) = state.world().stairs_destination(tile) else {
             return;
+        };
+        let coordinates = state.player().coordinate().on_level(level);
+        if state
+            .world()
+            .is_traversable(&coordinates, state.player().collision_bounds())
+        {
+            state.player_mut().set_coordinates(coordinates);
         }
+    }
 
-        state.player_mut().set_coordinates(new_coordinates);
+    /// The tile under the middle of the player's feet.
+    fn determine_standing_tile(player: &Player) -> TileCoordinate {
+        let bounds = player.collision_bounds();
+        player
+            .coordinate()
+            .shifted(bounds.width() / 2f32, bounds.height() / 2f32)
+            .tile()
+    }
+
+    fn update_camera(state: &mut GameState) {
+        let width = state.width().get::<pixel>();
+        let height = state.height().get::<pixel>();
+        let frame_duration = state.frame_duration();
+        let target = state.player().coordinate().clone();
+        // Keep the camera over the level the player is on.
+        let level = target.tile().z();
+        if state.camera().position().tile().z() != level {
+            let bounds = state.world().bounds(level);
+            state.camera_mut().set_bounds(bounds);
+        }
+        state
+            .camera_mut()
+            .follow(&target, width, height, frame_duration);
     }
 
     fn calculate_delta_x_y(input: &InputState, state: &GameState) -> (f32, f32) {
@@ -216,7 +200,13 @@ impl ApplicationPlugin {
         let frame_duration = state.frame_duration();
         let max_speed = Length::new::<meter>(3f32) / Time::new::<second>(1f32);
         let max_distance = frame_duration * max_speed;
-        let max_distance_px = max_distance.get::<pixel>();
+        // Tiles like water slow the player down.
+        let standing_tile = Self::determine_standing_tile(state.player());
+        let movement_cost = state
+            .world()
+            .get_tile_definition(standing_tile)
+            .map_or(1f32, TileDefinition::movement_cost);
+        let max_distance_px = max_distance.get::<pixel>() / movement_cost;
         delta_x *= max_distance_px;
         delta_y *= -max_distance_px;
         (delta_x, delta_y)
@@ -257,100 +247,113 @@ impl ApplicationPlugin {
         controller_state.left_joystick().y_ratio()
     }
 
-    fn render_direct(state: &GameState, buffer: &mut [Color<u8>]) {
-        let width = state.width();
-        let height = state.height();
-        let window_bounds = Rectangle::new(0f32, 0f32, height.get::<pixel>(), width.get::<pixel>());
+    fn render_direct(
+        &self,
+        state: &GameState,
+        status: &PlatformStatus,
+        target: &mut RenderTarget<'_>,
+    ) {
+        #[allow(clippy::cast_precision_loss)]
+        let window_bounds =
+            Rectangle::new(0f32, 0f32, target.height() as f32, target.width() as f32);
 
-        let world = state.world();
-        let player_coordinate = state.player().coordinate();
-        let start_coordinate = Self::determine_start_coordinate(world, player_coordinate);
+        Self::render_tilemap(state, &window_bounds, target).unwrap_or_default(); // Ignore errors
 
-        Self::render_tilemap(state, &window_bounds, &start_coordinate, buffer).unwrap_or_default(); // Ignore errors
+        self.render_player(state, &window_bounds, target)
+            .unwrap_or_default(); // Ignore errors
 
-        Self::render_player(state, &window_bounds, &start_coordinate, buffer).unwrap_or_default(); // Ignore errors
+        Self::render_hud(state, status, target);
+    }
+
+    /// Shows how long frames take, where the player is and what is being recorded in
+    /// the top-left corner.
+    fn render_hud(state: &GameState, status: &PlatformStatus, target: &mut RenderTarget<'_>) {
+        let frame_time = status.frame_time.get::<millisecond>();
+        let player = state.player().coordinate();
+        let mut lines = vec![
+            format!("Frame time: {frame_time:.1} ms"),
+            format!(
+                "Level {}, tile map ({}, {}), tile ({}, {})",
+                player.level(),
+                player.tile_map_x(),
+                player.tile_map_y(),
+                player.tile_x(),
+                player.tile_y()
+            ),
+        ];
+        match status.recording {
+            RecordingStatus::Idle => {}
+            RecordingStatus::Recording { slot, frame_count } => {
+                lines.push(format!("Recording slot {}: {frame_count} frames", slot + 1));
+            }
+            RecordingStatus::Playing {
+                slot,
+                position,
+                frame_count,
+                paused,
+            } => {
+                let source = slot.map_or_else(|| "file".to_string(), |s| format!("slot {}", s + 1));
+                let paused = if paused { " (paused)" } else { "" };
+                lines.push(format!(
+                    "Playing {source}: frame {position} of {frame_count}{paused}"
+                ));
+            }
+        }
+        let text = lines.join("\n");
+
+        // Draw the text on a translucent backdrop, so it stays readable over any tile.
+        let margin = 4;
+        let font = BitmapFont::embedded();
+        let style = TextStyle::new(Color::from_rgb(0xFF, 0xFF, 0xFF));
+        let (width, height) = font.measure(&text, &style);
+        let Some(top) = target.height().checked_sub(margin) else {
+            return;
+        };
+        let backdrop = Rectangle::new(
+            top.saturating_sub(height + margin / 2),
+            margin / 2,
+            height + margin,
+            width + margin,
+        );
+        target.blend_rectangle(backdrop, Color::from_rgba(0, 0, 0, 0xA0), BlendMode::Over);
+        #[allow(clippy::cast_possible_wrap)]
+        let top_left = Point2d::from_x_y(margin as isize, top as isize);
+        Rasterizer::new(target).text(font, &text, top_left, &style);
     }
 
     fn render_tilemap(
         state: &GameState,
         window_bounds: &Rectangle<f32>,
-        start_coordinate: &WorldCoordinate,
-        buffer: &mut [Color<u8>],
+        target: &mut RenderTarget<'_>,
     ) -> Result<()> {
-        // When rendering the tile map, our goal is to keep the player relatively close to the
-        // center of the screen. We also want to transition between tile maps smoothly, without
-        // suddenly jumping the user to a completely new screen. The player can also see surrounding
-        // tile maps and, without the presence of walls, they should be oblivious when they transit
-        // from one tile map to the other. Each tile map has a key, which is an (X, Y) coordinate
-        // of its relative position to other tile maps.
-        //
-        // Having relative tile map coordinates allows us to pick a maximum number of tiles up
-        // and down, left and right, to render at one time. We determine the player's current tile
-        // and count up to see if we need to start in a tile map above us. Similarly, we look to the
-        // left to see if we need to start in a tile map to our left. If the player moves toward the
-        // bottom or right of the screen, we want to spill over to the bottom and right tile map in
-        // a similar fashion.
-        //
-        // It's possible a tile map will not have a neighbor to the left, right, top, or bottom.
-        // When this happens, we switch our strategy, rendering more of the current tile map. This
-        // means once the player gets past the center, they will no longer stay in the center and
-        // start moving toward the outer edge. This avoids rendering a bunch of emptiness.
+        // The camera decides which part of the world is in view. We start from the tile
+        // under the bottom-left corner of the window and walk right and up, a tile at a
+        // time, until the window is covered. Tiles are counted from the world's origin,
+        // so the player cannot tell when they cross from one tile map to the next.
         let world = state.world();
         let player_coordinate = state.player().coordinate();
+        let origin = state
+            .camera()
+            .view_origin(window_bounds.width(), window_bounds.height());
+        let first_offset = origin.tile_offset();
 
-        let tile_size = world.tile_size;
-        let mut tile_map_y = start_coordinate.tile_map_y();
-        let mut tile_y = start_coordinate.tile_y();
-        for row_index in 0..world.rows {
-            let mut tile_map_x = start_coordinate.tile_map_x();
-            let mut tile_x = start_coordinate.tile_x();
-            for column_index in 0..world.columns {
-                #[allow(clippy::cast_precision_loss)]
-                let row_index = row_index as f32;
-                #[allow(clippy::cast_precision_loss)]
-                let column_index = column_index as f32;
-                let left = column_index * tile_size;
-                let bottom = row_index * tile_size;
-                let tile_rectangle = Rectangle::new(
-                    bottom.get::<pixel>(),
-                    left.get::<pixel>(),
-                    tile_size.get::<pixel>(),
-                    tile_size.get::<pixel>(),
-                );
-
-                let tile_map_key = TileMapKey {
-                    x: tile_map_x,
-                    y: tile_map_y,
-                };
-                let color = Self::determine_tile_color(
-                    world,
-                    player_coordinate,
-                    tile_map_key,
-                    tile_x,
-                    tile_y,
-                );
-                let tile_rectangle = tile_rectangle.moved_to(
-                    tile_rectangle.left(),
-                    state.height().get::<pixel>() - tile_rectangle.top(),
-                );
-                let tile_rectangle = tile_rectangle.shifted(
-                    world.x_offset.get::<pixel>(),
-                    -world.y_offset.get::<pixel>(),
-                );
-                Self::render_rectangle(window_bounds, &tile_rectangle, color, buffer)?;
-
-                tile_x += 1;
-                if tile_x >= world.columns {
-                    tile_map_x += 1;
-                    tile_x = 0;
-                }
+        let tile_size = world.tile_size().get::<pixel>();
+        let mut row_start = origin.tile();
+        let mut bottom = -first_offset.y();
+        while bottom < window_bounds.height() {
+            let mut tile = row_start;
+            let mut left = -first_offset.x();
+            while left < window_bounds.width() {
+                let tile_rectangle = Rectangle::new(bottom, left, tile_size, tile_size);
+                let color = Self::determine_tile_color(world, player_coordinate, tile);
+                Self::render_rectangle(window_bounds, &tile_rectangle, color, target)?;
+
+                left += tile_size;
+                tile = tile.shifted(1, 0);
             }
 
-            tile_y += 1;
-            if tile_y >= world.rows {
-                tile_map_y += 1;
-                tile_y = 0;
-            }
+            bottom += tile_size;
+            row_start = row_start.shifted(0, 1);
         }
         Ok(())
     }
@@ -358,23 +361,13 @@ impl ApplicationPlugin {
     fn determine_tile_color(
         world: &World,
         player_coordinate: &WorldCoordinate,
-        tile_map_key: TileMapKey,
-        tile_x: usize,
-        tile_y: usize,
+        tile: TileCoordinate,
     ) -> Color<f32> {
-        let tile = world
-            .get_tile_map(tile_map_key)
-            .map(|tm| tm[(tile_y, tile_x)]);
-        if let Some(tile) = tile {
-            if tile_map_key == player_coordinate.tile_map_key()
-                && tile_y == player_coordinate.tile_y()
-                && tile_x == player_coordinate.tile_x()
-            {
+        if let Some(definition) = world.get_tile_definition(tile) {
+            if tile == player_coordinate.tile() {
                 Color::from(Color::from_rgb(0x00, 0x00, 0x00)) // black
-            } else if tile == 0 {
-                Color::from(Color::from_rgb(0xCC, 0xCC, 0xCC)) // grey
             } else {
-                Color::from(Color::from_rgb(0xFF, 0xFF, 0xFF)) // white
+                Color::from(definition.color())
             }
         } else {
             Color::from(Color::from_rgb(0x00, 0x00, 0x00)) // black
@@ -382,129 +375,60 @@ impl ApplicationPlugin {
     }
 
     fn render_player(
+        &self,
         state: &GameState,
         window_bounds: &Rectangle<f32>,
-        start_coordinate: &WorldCoordinate,
-        buffer: &mut [Color<u8>],
+        target: &mut RenderTarget<'_>,
     ) -> Result<()> {
-        let world = state.world();
         let player = state.player();
-        let player_coordinate = player.coordinate();
-        let tile_size = world.tile_size().get::<pixel>();
-        let x_offset = Self::determine_player_offset(
-            start_coordinate.tile_map_x(),
-            start_coordinate.tile_x(),
-            player_coordinate.tile_map_x(),
-            player_coordinate.tile_x(),
-            tile_size,
-            world.columns(),
-        );
-        let y_offset = Self::determine_player_offset(
-            start_coordinate.tile_map_y(),
-            start_coordinate.tile_y(),
-            player_coordinate.tile_map_y(),
-            player_coordinate.tile_y(),
-            tile_size,
-            world.rows(),
+        let position = state.camera().project(
+            player.coordinate(),
+            window_bounds.width(),
+            window_bounds.height(),
         );
-
-        let player_bounds = player.render_bounds();
-        let player_bounds = player_bounds.shifted(x_offset, y_offset);
-        let height = state.height();
-        let player_bounds = player_bounds.moved_to(
-            player_bounds.left(),
-            height.get::<pixel>() - player_bounds.top(),
-        );
-        let player_bounds = player_bounds.shifted(
-            world.x_offset.get::<pixel>(),
-            -world.y_offset.get::<pixel>(),
+        let render_bounds = player.render_bounds();
+        let player_bounds = Rectangle::new(
+            position.y(),
+            position.x(),
+            render_bounds.height(),
+            render_bounds.width(),
         );
-        Self::render_rectangle(window_bounds, &player_bounds, player.color(), buffer)
-    }
-
-    #[allow(clippy::cast_precision_loss)]
-    #[allow(clippy::cast_possible_wrap)]
-    fn determine_player_offset(
-        start_tile_map: isize,
-        start_tile: usize,
-        tile_map: isize,
-        tile: usize,
-        tile_size: f32,
-        max_tile: usize,
-    ) -> f32 {
-        let tile_map_diff = tile_map as f32 - start_tile_map as f32;
-        let tile_map_diff = tile_map_diff * max_tile as f32 * tile_size;
-        let tile_diff = tile as f32 - start_tile as f32;
-        let tile_diff = tile_diff * tile_size;
-        tile_map_diff + tile_diff
-    }
-
-    fn determine_start_coordinate(
-        world: &World,
-        player_coordinate: &WorldCoordinate,
-    ) -> WorldCoordinate {
-        let tile_map_x = player_coordinate.tile_map_x();
-        let tile_x = player_coordinate.tile_x();
-        let (start_tile_map_x, start_tile_x) =
-            Self::determine_start(tile_map_x, tile_x, world.columns());
-
-        let tile_map_y = player_coordinate.tile_map_y();
-        let tile_y = player_coordinate.tile_y();
-        let (start_tile_map_y, start_tile_y) =
-            Self::determine_start(tile_map_y, tile_y, world.rows());
-
-        let start_tile_map_key = TileMapKey {
-            x: start_tile_map_x,
-            y: start_tile_map_y,
+        let sprites = self
+            .player_sprites
+            .get_or_init(|| PlayerSprites::new(player).ok());
+        let Some((sprites, frame)) = sprites
+            .as_ref()
+            .and_then(|s| s.frame(player).map(|frame| (s, frame)))
+        else {
+            return Self::render_rectangle(window_bounds, &player_bounds, player.color(), target);
         };
-        let tile_map_coordinates = TileMapCoordinate::at_x_y(start_tile_x, start_tile_y);
-        WorldCoordinate::new(world, start_tile_map_key, tile_map_coordinates)
-    }
-
-    fn determine_start(tile_map: isize, tile: usize, max_tile: usize) -> (isize, usize) {
-        let middle = max_tile / 2;
-        match tile.cmp(&middle) {
-            Ordering::Less => (tile_map - 1, max_tile - (middle - tile)),
-            Ordering::Greater => (tile_map, tile - middle),
-            Ordering::Equal => (tile_map, 0),
-        }
+        #[allow(clippy::cast_possible_truncation)]
+        let bottom_left = Point2d::from_x_y(
+            player_bounds.left().round() as isize,
+            player_bounds.bottom().round() as isize,
+        );
+        Rasterizer::new(target).sprite(sprites.sheet(), frame, bottom_left);
+        Ok(())
     }
 
     fn render_rectangle(
         window_bounds: &Rectangle<f32>,
         rectangle: &Rectangle<f32>,
         color: Color<f32>,
-        buffer: &mut [Color<u8>],
+        target: &mut RenderTarget<'_>,
     ) -> Result<()> {
         let rectangle = rectangle.bound_to(window_bounds);
         let rectangle = rectangle.round_to_usize()?;
-        if rectangle.width() == 0 {
-            // Avoid iterating over rows when there are no columns.
-            return Ok(());
-        }
-
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        let pitch = window_bounds.width() as usize;
-        let color = Color::from(color);
-        let mut index = rectangle.bottom() * pitch + rectangle.left();
-        for _y in rectangle.bottom()..rectangle.top() {
-            let row = index;
-            for _x in rectangle.left()..rectangle.right() {
-                buffer[index] = color;
-                index += 1;
-            }
-            index = row + pitch;
-        }
+        target.blend_rectangle(rectangle, Color::from(color), BlendMode::Over);
         Ok(())
     }
 }
 
 impl Application for ApplicationPlugin {
     #[inline]
-    fn initialize(&self, context: InitializeContext<'_>) {
+    fn initialize(&self, context: InitializeContext<'_>) -> Result<()> {
         let InitializeContext { state } = context;
-        Self::initialize_direct(state);
+        Self::initialize_direct(state)
     }
 
     #[inline]
@@ -518,12 +442,134 @@ impl Application for ApplicationPlugin {
         let RenderContext {
             input: _input,
             state,
-            buffer,
+            mut target,
+            status,
         } = context;
 
-        Self::render_direct(state, buffer);
+        self.render_direct(state, &status, &mut target);
     }
 
     #[inline]
     fn write_sound(&self, _context: AudioContext<'_>) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::application_plugin::ApplicationPlugin;
+    use crate::golden_image::{Image, assert_matches_golden};
+    use handmade_hero_interface::color::Color;
+    use handmade_hero_interface::direction::Direction;
+    use handmade_hero_interface::game_state::GameState;
+    use handmade_hero_interface::input_state::InputState;
+    use handmade_hero_interface::platform_status::{PlatformStatus, RecordingStatus};
+    use handmade_hero_interface::render_target::{RenderTarget, RowOrder};
+    use handmade_hero_interface::tile_coordinate::TileCoordinate;
+    use handmade_hero_interface::units::si::length::{Length, pixel};
+    use handmade_hero_interface::units::si::time::Time;
+    use handmade_hero_interface::world_coordinate::WorldCoordinate;
+    use uom::si::time::second;
+
+    const WIDTH: u16 = 640;
+    const HEIGHT: u16 = 360;
+
+    /// Renders the starting world after moving the player by the given number of tiles.
+    fn render_shifted(tiles_x: f32, tiles_y: f32) -> Image {
+        render_shifted_with(tiles_x, tiles_y, |_, _| {})
+    }
+
+    fn render_shifted_with(
+        tiles_x: f32,
+        tiles_y: f32,
+        update: impl FnOnce(&mut GameState, &mut PlatformStatus),
+    ) -> Image {
+        let mut state = GameState::new();
+        state.set_width(Length::new::<pixel>(f32::from(WIDTH)));
+        state.set_height(Length::new::<pixel>(f32::from(HEIGHT)));
+        ApplicationPlugin::initialize_direct(&mut state).unwrap();
+        let tile_size = state.world().tile_size().get::<pixel>();
+        let coordinate = state
+            .player()
+            .coordinate()
+            .shifted(tiles_x * tile_size, tiles_y * tile_size);
+        state.player_mut().set_coordinates(coordinate);
+        ApplicationPlugin::update_camera(&mut state);
+        let mut status = PlatformStatus::default();
+        update(&mut state, &mut status);
+        render(&state, &status)
+    }
+
+    fn render(state: &GameState, status: &PlatformStatus) -> Image {
+        let (width, height) = (usize::from(WIDTH), usize::from(HEIGHT));
+        let mut buffer = vec![Color::default(); width * height];
+        let mut target =
+            RenderTarget::new(&mut buffer, width, height, width, RowOrder::TopDown).unwrap();
+        ApplicationPlugin::default().render_direct(state, status, &mut target);
+        Image::from_target(&target)
+    }
+
+    #[test]
+    fn test_render_start() {
+        assert_matches_golden("start", &render_shifted(0f32, 0f32), 0);
+    }
+
+    #[test]
+    fn test_render_scrolled_into_east_tile_map() {
+        // The view should spill over into the tile map to the east.
+        assert_matches_golden("east", &render_shifted(6f32, 0f32), 0);
+    }
+
+    #[test]
+    fn test_render_scrolled_into_south_tile_map() {
+        // Below the middle row, the view starts in the tile map to the south.
+        assert_matches_golden("south", &render_shifted(1f32, -2f32), 0);
+    }
+
+    #[test]
+    fn test_render_walking_left() {
+        // Mid-stride, on the second frame of the walk cycle, while playing back.
+        let image = render_shifted_with(0f32, 0f32, |state, status| {
+            state
+                .player_mut()
+                .walk(Direction::Left, Time::new::<second>(0.2f32));
+            status.frame_time = Time::new::<second>(0.0125f32);
+            status.recording = RecordingStatus::Playing {
+                slot: Some(1),
+                position: 30,
+                frame_count: 120,
+                paused: true,
+            };
+        });
+        assert_matches_golden("walking_left", &image, 0);
+    }
+
+    #[test]
+    fn test_stairs_lead_to_the_level_above() {
+        let mut state = GameState::new();
+        state.set_width(Length::new::<pixel>(f32::from(WIDTH)));
+        state.set_height(Length::new::<pixel>(f32::from(HEIGHT)));
+        state.set_frame_duration(Time::new::<second>(1f32 / 30f32));
+        ApplicationPlugin::initialize_direct(&mut state).unwrap();
+        // Just below the hub's stairs, walking up.
+        let below_stairs = TileCoordinate::new(8, 5, 0);
+        let coordinate = WorldCoordinate::from_tile(state.world(), below_stairs);
+        state
+            .player_mut()
+            .set_coordinates(coordinate.shifted(8f32, 8f32));
+        let mut input = InputState::new();
+        input.keyboard_mut().up_mut().set_ended_down(true);
+
+        let mut frames = 0;
+        while state.player().coordinate().level() == 0 {
+            assert!(frames < 30, "The player never reached the stairs");
+            ApplicationPlugin::process_input_direct(&input, &mut state);
+            frames += 1;
+        }
+        // Stepping off the stairs at the top does not lead back down.
+        for _ in 0..10 {
+            ApplicationPlugin::process_input_direct(&input, &mut state);
+        }
+        assert_eq!(1, state.player().coordinate().level());
+        assert_eq!(1, state.camera().position().level());
+        assert_matches_golden("upstairs", &render(&state, &PlatformStatus::default()), 0);
+    }
+}
@@ -0,0 +1,172 @@
+//! Compares rendered frames against golden images checked in under `golden/`.
+//!
+//! Images are stored as binary PPM files, which any image viewer can open. Set the
+//! `UPDATE_GOLDEN` environment variable to rewrite the golden images from the current
+//! output after an intentional rendering change. When a comparison fails, the actual
+//! image and a diff image highlighting every mismatched pixel in red are written to
+//! `target/golden/`.
+
+use handmade_hero_interface::render_target::RenderTarget;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Image {
+    width: usize,
+    height: usize,
+    /// RGB triples, top row first.
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub(crate) fn from_target(target: &RenderTarget<'_>) -> Self {
+        let pixels = (0..target.height())
+            .rev()
+            .filter_map(|y| target.row(y))
+            .flatten()
+            .map(|c| [c.red(), c.green(), c.blue()])
+            .collect();
+        Self {
+            width: target.width(),
+            height: target.height(),
+            pixels,
+        }
+    }
+
+    fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    fn from_ppm(bytes: &[u8]) -> Option<Self> {
+        // The header is "P6", the width, the height and the maximum value, separated
+        // by whitespace, followed by a single whitespace character.
+        let mut fields = Vec::new();
+        let mut start = 0;
+        let mut index = 0;
+        while fields.len() < 4 {
+            let byte = *bytes.get(index)?;
+            if byte.is_ascii_whitespace() {
+                if index > start {
+                    fields.push(std::str::from_utf8(&bytes[start..index]).ok()?);
+                }
+                start = index + 1;
+            }
+            index += 1;
+        }
+        if fields[0] != "P6" || fields[3] != "255" {
+            return None;
+        }
+        let width: usize = fields[1].parse().ok()?;
+        let height: usize = fields[2].parse().ok()?;
+        let data = bytes.get(index..index + width * height * 3)?;
+        let pixels = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+/// Panics unless the image matches the named golden image. Pixels match if none of
+/// their channels differ by more than the tolerance.
+pub(crate) fn assert_matches_golden(name: &str, actual: &Image, tolerance: u8) {
+    let golden_path = manifest_directory()
+        .join("golden")
+        .join(format!("{name}.ppm"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&golden_path, actual.to_ppm()).unwrap();
+        return;
+    }
+
+    let golden = std::fs::read(&golden_path)
+        .ok()
+        .and_then(|bytes| Image::from_ppm(&bytes))
+        .unwrap_or_else(|| {
+            panic!(
+                "Could not read {}. Run the tests with UPDATE_GOLDEN=1 to create it.",
+                golden_path.display()
+            )
+        });
+    if golden.width != actual.width || golden.height != actual.height {
+        write_failure(name, actual, None);
+        panic!(
+            "{name} is {}x{} but the golden image is {}x{}",
+            actual.width, actual.height, golden.width, golden.height
+        );
+    }
+
+    let is_match =
+        |a: &[u8; 3], b: &[u8; 3]| a.iter().zip(b).all(|(a, b)| a.abs_diff(*b) <= tolerance);
+    let mismatches = golden
+        .pixels
+        .iter()
+        .zip(&actual.pixels)
+        .filter(|(g, a)| !is_match(g, a))
+        .count();
+    if mismatches > 0 {
+        let diff = Image {
+            width: actual.width,
+            height: actual.height,
+            pixels: golden
+                .pixels
+                .iter()
+                .zip(&actual.pixels)
+                .map(|(g, a)| {
+                    if is_match(g, a) {
+                        // Fade matching pixels so the mismatches stand out.
+                        g.map(|c| c / 4)
+                    } else {
+                        [0xFF, 0x00, 0x00]
+                    }
+                })
+                .collect(),
+        };
+        write_failure(name, actual, Some(&diff));
+        panic!(
+            "{mismatches} pixels of {name} differ from {} (see target/golden)",
+            golden_path.display()
+        );
+    }
+}
+
+fn write_failure(name: &str, actual: &Image, diff: Option<&Image>) {
+    let directory = manifest_directory().join("../target/golden");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(
+        directory.join(format!("{name}.actual.ppm")),
+        actual.to_ppm(),
+    )
+    .unwrap();
+    if let Some(diff) = diff {
+        std::fs::write(directory.join(format!("{name}.diff.ppm")), diff.to_ppm()).unwrap();
+    }
+}
+
+fn manifest_directory() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::golden_image::Image;
+    use handmade_hero_interface::color::Color;
+    use handmade_hero_interface::render_target::{RenderTarget, RowOrder};
+
+    #[test]
+    fn test_ppm_round_trip() {
+        let mut buffer = [
+            Color::from_rgb(1, 2, 3),
+            Color::from_rgb(4, 5, 6),
+            Color::from_rgb(7, 8, 9),
+            Color::from_rgb(10, 11, 12),
+        ];
+        let target = RenderTarget::new(&mut buffer, 2, 2, 2, RowOrder::BottomUp).unwrap();
+        let image = Image::from_target(&target);
+        // Images are stored top row first.
+        assert_eq!([7, 8, 9], image.pixels[0]);
+        let read = Image::from_ppm(&image.to_ppm()).unwrap();
+        assert_eq!(image, read);
+    }
+}
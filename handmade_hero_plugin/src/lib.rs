@@ -1 +1,4 @@
 mod application_plugin;
+#[cfg(test)]
+mod golden_image;
+mod player_sprites;
@@ -0,0 +1,149 @@
+use handmade_hero_interface::animation_clip::AnimationClip;
+use handmade_hero_interface::application_error::Result;
+use handmade_hero_interface::bitmap::Bitmap;
+use handmade_hero_interface::color::Color;
+use handmade_hero_interface::direction::Direction;
+use handmade_hero_interface::player::Player;
+use handmade_hero_interface::rasterizer::Rasterizer;
+use handmade_hero_interface::rectangle::Rectangle;
+use handmade_hero_interface::sprite_sheet::SpriteSheet;
+use handmade_hero_interface::units::si::time::Time;
+use uom::num::Zero;
+use uom::si::time::second;
+
+/// The frames of each walk cycle. The first is also the standing pose.
+const WALK_FRAMES: usize = 4;
+const WALK_FRAME_SECONDS: f32 = 0.15;
+
+/// The player's sprite sheet and walk cycles, one row of the sheet per direction.
+///
+/// Until we have real art, the sheet is drawn when the plugin starts, sized to fit the
+/// player's render bounds.
+#[derive(Debug)]
+pub(crate) struct PlayerSprites {
+    sheet: SpriteSheet,
+    walk_clips: Vec<(Direction, AnimationClip)>,
+}
+
+impl PlayerSprites {
+    pub(crate) fn new(player: &Player) -> Result<Self> {
+        let bounds = player.render_bounds();
+        #[allow(clippy::cast_sign_loss)]
+        #[allow(clippy::cast_possible_truncation)]
+        let (frame_width, frame_height) = (
+            bounds.width().round().max(1f32) as usize,
+            bounds.height().round().max(1f32) as usize,
+        );
+        let rows = Direction::ALL.len();
+        let width = frame_width * WALK_FRAMES;
+        let height = frame_height * rows;
+        let mut bitmap = Bitmap::new(width, height, vec![Color::default(); width * height])?;
+        let body = Color::from(player.color());
+        let mut walk_clips = Vec::with_capacity(rows);
+        for (row, direction) in Direction::ALL.into_iter().enumerate() {
+            for column in 0..WALK_FRAMES {
+                let frame = Rectangle::new(
+                    height - (row + 1) * frame_height,
+                    column * frame_width,
+                    frame_height,
+                    frame_width,
+                );
+                let mut target = bitmap.target();
+                if let Some(mut view) = target.view(frame) {
+                    Self::draw_frame(&mut Rasterizer::new(&mut view), direction, column, body);
+                }
+            }
+            let first = row * WALK_FRAMES;
+            let clip = AnimationClip::uniform(
+                first..first + WALK_FRAMES,
+                Time::new::<second>(WALK_FRAME_SECONDS),
+            )?;
+            walk_clips.push((direction, clip));
+        }
+        let sheet = SpriteSheet::from_grid(bitmap, frame_width, frame_height)?;
+        Ok(Self { sheet, walk_clips })
+    }
+
+    #[inline]
+    pub(crate) fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    /// The frame of the sheet showing the player as they currently are.
+    pub(crate) fn frame(&self, player: &Player) -> Option<usize> {
+        let (_, clip) = self
+            .walk_clips
+            .iter()
+            .find(|(direction, _)| *direction == player.facing())?;
+        if player.walk_duration() == Time::zero() {
+            clip.frames().first().map(|f| f.sprite)
+        } else {
+            Some(clip.sprite_at(player.walk_duration()))
+        }
+    }
+
+    /// Draws a figure facing the direction. Every other step lifts one leg.
+    fn draw_frame(
+        rasterizer: &mut Rasterizer<'_, '_>,
+        direction: Direction,
+        step: usize,
+        body: Color<u8>,
+    ) {
+        let hair = Color::from_rgb(0x60, 0x40, 0x20);
+        let eyes = Color::from_rgb(0x20, 0x20, 0x20);
+        let legs = Color::from_rgb(0x30, 0x30, 0x80);
+
+        let width = rasterizer.width();
+        let height = rasterizer.height();
+        let leg_height = (height / 5).max(1);
+        let margin = width / 6;
+        let body_width = width - 2 * margin;
+        let head_top = height - 1;
+        rasterizer.fill_rectangle(
+            Rectangle::new(leg_height, margin, height - leg_height, body_width),
+            body,
+        );
+
+        // Seen from behind, the hair covers most of the head.
+        let hair_height = if direction == Direction::Up {
+            height / 4
+        } else {
+            height / 8
+        };
+        rasterizer.fill_rectangle(
+            Rectangle::new(height - hair_height, margin, hair_height, body_width),
+            hair,
+        );
+
+        let eye_size = (width / 10).max(1);
+        let eye_bottom = head_top - height / 5;
+        let eye_lefts = match direction {
+            Direction::Up => vec![],
+            Direction::Down => vec![width / 3, width * 2 / 3 - eye_size],
+            Direction::Left => vec![margin + eye_size],
+            Direction::Right => vec![width - margin - 2 * eye_size],
+        };
+        for left in eye_lefts {
+            rasterizer.fill_rectangle(Rectangle::new(eye_bottom, left, eye_size, eye_size), eyes);
+        }
+
+        let leg_width = (body_width / 2 - 1).max(1);
+        let lifted = match step {
+            1 => Some(0),
+            3 => Some(1),
+            _ => None,
+        };
+        for leg in 0..2 {
+            let left = margin + leg * (body_width - leg_width);
+            let bottom = if lifted == Some(leg) {
+                leg_height / 2
+            } else {
+                0
+            };
+            rasterizer.fill_rectangle(
+                Rectangle::new(bottom, left, leg_height - bottom, leg_width),
+                legs,
+            );
+        }
+    }
+}
@@ -0,0 +1,545 @@
+use handmade_hero_interface::application_error::ApplicationError;
+use serde::Serialize;
+use serde::ser::{
+    Error as SerError, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// A field whose value differs between two serializable values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDifference {
+    pub path: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl Display for FieldDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let expected = self.expected.as_deref().unwrap_or("<missing>");
+        let actual = self.actual.as_deref().unwrap_or("<missing>");
+        write!(f, "{}: expected {expected} but was {actual}", self.path)
+    }
+}
+
+/// Flattens both values into their leaf fields and returns the first field, in the order
+/// the fields are serialized, whose value differs. Fields whose path starts with one of
+/// the ignored prefixes are skipped. Map entries are ordered by their serialized key, so
+/// the iteration order of a `HashMap` does not matter.
+///
+/// # Errors
+/// An error is returned if either value cannot be serialized.
+pub fn first_difference<T: Serialize>(
+    expected: &T,
+    actual: &T,
+    ignored: &[&str],
+) -> Result<Option<FieldDifference>, ApplicationError> {
+    let flatten = |value| {
+        flatten(value).map_err(|e| ApplicationError::wrap("Could not compare the values", e))
+    };
+    let expected = flatten(expected)?;
+    let actual = flatten(actual)?;
+    let mut unmatched = actual
+        .iter()
+        .map(|(path, value)| (path.as_str(), value))
+        .collect::<HashMap<_, _>>();
+    let is_ignored = |path: &str| ignored.iter().any(|prefix| path.starts_with(prefix));
+    for (path, expected_value) in &expected {
+        if is_ignored(path) {
+            continue;
+        }
+        let actual_value = unmatched.remove(path.as_str());
+        if actual_value != Some(expected_value) {
+            return Ok(Some(FieldDifference {
+                path: path.clone(),
+                expected: Some(expected_value.clone()),
+                actual: actual_value.cloned(),
+            }));
+        }
+    }
+    Ok(actual
+        .iter()
+        .find(|(path, _)| unmatched.contains_key(path.as_str()) && !is_ignored(path))
+        .map(|(path, value)| FieldDifference {
+            path: path.clone(),
+            expected: None,
+            actual: Some(value.clone()),
+        }))
+}
+
+/// The leaf fields of the value, as paths and values, in the order they are serialized.
+fn flatten<T: Serialize + ?Sized>(value: &T) -> Result<Vec<(String, String)>, FlattenError> {
+    let mut collector = FieldCollector::default();
+    value.serialize(FieldSerializer {
+        collector: &mut collector,
+        path: String::new(),
+    })?;
+    Ok(collector.fields)
+}
+
+#[derive(Debug, Default)]
+struct FieldCollector {
+    fields: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+struct FlattenError(String);
+
+impl Display for FlattenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for FlattenError {}
+
+impl SerError for FlattenError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self(message.to_string())
+    }
+}
+
+#[derive(Debug)]
+struct FieldSerializer<'a> {
+    collector: &'a mut FieldCollector,
+    path: String,
+}
+
+impl<'a> FieldSerializer<'a> {
+    #[allow(clippy::unnecessary_wraps)]
+    fn leaf(self, value: impl Display) -> Result<(), FlattenError> {
+        self.collector.fields.push((self.path, value.to_string()));
+        Ok(())
+    }
+
+    fn compound(self) -> Compound<'a> {
+        Compound {
+            collector: self.collector,
+            path: self.path,
+            index: 0,
+            key: None,
+            entries: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Compound<'a> {
+    collector: &'a mut FieldCollector,
+    path: String,
+    index: usize,
+    key: Option<String>,
+    /// The fields of each map entry, by key, so they can be sorted once the map ends.
+    entries: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl Compound<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        let path = format!("{}[{}]", self.path, self.index);
+        self.index += 1;
+        self.child(path, value)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), FlattenError> {
+        let path = if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{name}", self.path)
+        };
+        self.child(path, value)
+    }
+
+    fn child<T: Serialize + ?Sized>(
+        &mut self,
+        path: String,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        value.serialize(FieldSerializer {
+            collector: self.collector,
+            path,
+        })
+    }
+}
+
+impl<'a> Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = FlattenError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), FlattenError> {
+        self.leaf(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), FlattenError> {
+        // Debug formatting round-trips, so values that print the same are identical.
+        self.leaf(format!("{v:?}"))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), FlattenError> {
+        self.leaf(format!("{v:?}"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), FlattenError> {
+        self.leaf(format!("{v:?}"))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), FlattenError> {
+        self.leaf(format!("{v:?}"))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), FlattenError> {
+        self.leaf(format!("{v:?}"))
+    }
+
+    fn serialize_none(self) -> Result<(), FlattenError> {
+        self.leaf("None")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), FlattenError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), FlattenError> {
+        self.leaf("()")
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), FlattenError> {
+        self.leaf(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), FlattenError> {
+        self.leaf(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        self.compound().field(variant, value)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        let mut compound = self.compound();
+        compound.path = format!("{}::{variant}", compound.path);
+        Ok(compound)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FlattenError> {
+        let mut compound = self.compound();
+        compound.path = format!("{}::{variant}", compound.path);
+        Ok(compound)
+    }
+}
+
+impl SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), FlattenError> {
+        // Struct keys, like a tile map's key, are flattened into a single label.
+        let fields = flatten(key)?;
+        let label = fields
+            .into_iter()
+            .map(|(path, value)| {
+                if path.is_empty() {
+                    value
+                } else {
+                    format!("{path}={value}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        self.key = Some(label);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FlattenError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| FlattenError::custom("A map value was serialized before its key"))?;
+        let mut collector = FieldCollector::default();
+        value.serialize(FieldSerializer {
+            collector: &mut collector,
+            path: format!("{}[{key}]", self.path),
+        })?;
+        self.entries.push((key, collector.fields));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<(), FlattenError> {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, fields) in self.entries {
+            self.collector.fields.extend(fields);
+        }
+        Ok(())
+    }
+}
+
+impl SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+impl SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FlattenError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), FlattenError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), FlattenError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field_diff::first_difference;
+    use serde::Serialize;
+    use std::collections::HashMap;
+
+    #[derive(Serialize, PartialEq, Eq, Hash)]
+    struct Key {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Serialize)]
+    struct Sample {
+        position: (f32, f32),
+        rooms: HashMap<Key, Vec<u8>>,
+    }
+
+    fn sample() -> Sample {
+        let mut rooms = HashMap::new();
+        rooms.insert(Key { x: 0, y: 0 }, vec![0, 1]);
+        rooms.insert(Key { x: 1, y: 0 }, vec![1, 1]);
+        Sample {
+            position: (1.5, 2.5),
+            rooms,
+        }
+    }
+
+    #[test]
+    fn test_identical_values_have_no_difference() {
+        assert_eq!(None, first_difference(&sample(), &sample(), &[]).unwrap());
+    }
+
+    #[test]
+    fn test_reports_path_of_first_difference() {
+        let mut actual = sample();
+        actual.rooms.get_mut(&Key { x: 1, y: 0 }).unwrap()[1] = 0;
+        let difference = first_difference(&sample(), &actual, &[]).unwrap().unwrap();
+        assert_eq!("rooms[x=1,y=0][1]", difference.path);
+        assert_eq!(Some("1"), difference.expected.as_deref());
+        assert_eq!(Some("0"), difference.actual.as_deref());
+    }
+
+    #[test]
+    fn test_ignores_prefixes() {
+        let mut actual = sample();
+        actual.position.0 = 3.0;
+        assert!(
+            first_difference(&sample(), &actual, &["position"])
+                .unwrap()
+                .is_none()
+        );
+        let difference = first_difference(&sample(), &actual, &[]).unwrap().unwrap();
+        assert_eq!("position[0]", difference.path);
+    }
+
+    #[derive(Serialize)]
+    struct Ordered {
+        zebra: i32,
+        apple: i32,
+        items: Vec<u8>,
+    }
+
+    #[test]
+    fn test_reports_first_field_in_serialization_order() {
+        let expected = Ordered {
+            zebra: 1,
+            apple: 1,
+            items: vec![0; 11],
+        };
+        let mut actual = Ordered {
+            zebra: 2,
+            apple: 2,
+            items: vec![0; 11],
+        };
+        let difference = first_difference(&expected, &actual, &[]).unwrap().unwrap();
+        assert_eq!("zebra", difference.path);
+
+        actual.zebra = 1;
+        actual.apple = 1;
+        actual.items[10] = 1;
+        actual.items[2] = 1;
+        let difference = first_difference(&expected, &actual, &[]).unwrap().unwrap();
+        assert_eq!("items[2]", difference.path);
+    }
+}
@@ -0,0 +1,252 @@
+use handmade_hero_interface::application::Application;
+use handmade_hero_interface::application_error::Result;
+use handmade_hero_interface::audio_context::AudioContext;
+use handmade_hero_interface::color::Color;
+use handmade_hero_interface::game_state::GameState;
+use handmade_hero_interface::initialize_context::InitializeContext;
+use handmade_hero_interface::input_context::InputContext;
+use handmade_hero_interface::input_state::InputState;
+use handmade_hero_interface::platform_status::PlatformStatus;
+use handmade_hero_interface::render_context::RenderContext;
+use handmade_hero_interface::render_target::{RenderTarget, RowOrder};
+use handmade_hero_interface::stereo_sample::StereoSample;
+use handmade_hero_interface::units::si::length::{Length, pixel};
+use handmade_hero_interface::units::si::time::Time;
+use uom::si::time::second;
+
+/// Drives an `Application` without a window, sound device or real clock. Each frame
+/// is simulated at a fixed rate, rendering into an in-memory bitmap and writing
+/// exactly one frame's worth of audio samples.
+#[derive(Debug)]
+pub struct HeadlessApplication {
+    state: GameState,
+    input: InputState,
+    bitmap_buffer: Vec<Color<u8>>,
+    bitmap_width: usize,
+    bitmap_height: usize,
+    sound_buffer: Vec<StereoSample>,
+    frame_count: u64,
+}
+
+impl HeadlessApplication {
+    #[must_use]
+    pub fn new(width: u16, height: u16, frames_per_second: u16) -> Self {
+        let mut state = GameState::new();
+        state.set_width(Length::new::<pixel>(f32::from(width)));
+        state.set_height(Length::new::<pixel>(f32::from(height)));
+        state.set_frame_duration(Time::new::<second>(1f32 / f32::from(frames_per_second)));
+        Self::from_state(state)
+    }
+
+    /// Creates an application that continues from an existing state, such as one
+    /// restored from a recording. The buffers are sized using the state's dimensions
+    /// and frame duration.
+    #[must_use]
+    pub fn from_state(state: GameState) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let width = state.width().get::<pixel>() as usize;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let height = state.height().get::<pixel>() as usize;
+        let bitmap_buffer = vec![Color::default(); width * height];
+
+        #[allow(clippy::cast_precision_loss)]
+        let samples_per_second = state.sound().samples_per_second() as f32;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let samples_per_frame =
+            (samples_per_second * state.frame_duration().get::<second>()).round() as usize;
+        let sound_buffer = vec![StereoSample::default(); samples_per_frame];
+
+        Self {
+            state,
+            input: InputState::new(),
+            bitmap_buffer,
+            bitmap_width: width,
+            bitmap_height: height,
+            sound_buffer,
+            frame_count: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn state_mut(&mut self) -> &mut GameState {
+        &mut self.state
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn input(&self) -> &InputState {
+        &self.input
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn input_mut(&mut self) -> &mut InputState {
+        &mut self.input
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bitmap_buffer(&self) -> &[Color<u8>] {
+        &self.bitmap_buffer
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bitmap_width(&self) -> usize {
+        self.bitmap_width
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn bitmap_height(&self) -> usize {
+        self.bitmap_height
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn sound_buffer(&self) -> &[StereoSample] {
+        &self.sound_buffer
+    }
+
+    /// The number of frames simulated since the application was created.
+    #[inline]
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// # Errors
+    /// An error is returned if the application fails to initialize.
+    pub fn initialize(&mut self, application: &dyn Application) -> Result<()> {
+        let context = InitializeContext {
+            state: &mut self.state,
+        };
+        application.initialize(context)
+    }
+
+    /// Runs a single frame of the game loop using whatever input is currently set.
+    pub fn run_frame(&mut self, application: &dyn Application) {
+        let context = InputContext {
+            input: &self.input,
+            state: &mut self.state,
+        };
+        application.process_input(context);
+
+        let context = RenderContext {
+            input: &self.input,
+            state: &mut self.state,
+            target: Self::render_target(
+                &mut self.bitmap_buffer,
+                self.bitmap_width,
+                self.bitmap_height,
+            ),
+            status: PlatformStatus::default(),
+        };
+        application.render(context);
+
+        let context = AudioContext {
+            state: &mut self.state,
+            sound_buffer: &mut self.sound_buffer,
+        };
+        application.write_sound(context);
+
+        self.frame_count += 1;
+    }
+
+    fn render_target(
+        bitmap_buffer: &mut [Color<u8>],
+        width: usize,
+        height: usize,
+    ) -> RenderTarget<'_> {
+        // Rows are stored top-down, the same as the Windows host's bitmap.
+        RenderTarget::new(bitmap_buffer, width, height, width, RowOrder::TopDown)
+            .expect("The bitmap buffer is sized to fit the render target")
+    }
+
+    /// Initializes the application and then runs the requested number of frames.
+    ///
+    /// # Errors
+    /// An error is returned if the application fails to initialize.
+    pub fn run(&mut self, application: &dyn Application, frame_count: u64) -> Result<()> {
+        self.initialize(application)?;
+        for _ in 0..frame_count {
+            self.run_frame(application);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::headless_application::HeadlessApplication;
+    use handmade_hero_interface::application::Application;
+    use handmade_hero_interface::application_error::Result;
+    use handmade_hero_interface::audio_context::AudioContext;
+    use handmade_hero_interface::initialize_context::InitializeContext;
+    use handmade_hero_interface::input_context::InputContext;
+    use handmade_hero_interface::render_context::RenderContext;
+    use std::cell::Cell;
+
+    #[derive(Debug, Default)]
+    struct CountingApplication {
+        initialized: Cell<usize>,
+        inputs: Cell<usize>,
+        renders: Cell<usize>,
+        sounds: Cell<usize>,
+        last_buffer_length: Cell<usize>,
+        last_sample_count: Cell<usize>,
+    }
+
+    impl Application for CountingApplication {
+        fn initialize(&self, _context: InitializeContext<'_>) -> Result<()> {
+            self.initialized.set(self.initialized.get() + 1);
+            Ok(())
+        }
+
+        fn process_input(&self, _context: InputContext<'_>) {
+            self.inputs.set(self.inputs.get() + 1);
+        }
+
+        fn render(&self, context: RenderContext<'_>) {
+            self.renders.set(self.renders.get() + 1);
+            let target = &context.target;
+            self.last_buffer_length
+                .set(target.width() * target.height());
+        }
+
+        fn write_sound(&self, context: AudioContext<'_>) {
+            self.sounds.set(self.sounds.get() + 1);
+            self.last_sample_count.set(context.sound_buffer.len());
+        }
+    }
+
+    #[test]
+    fn test_run_calls_each_stage_once_per_frame() {
+        let application = CountingApplication::default();
+        let mut headless = HeadlessApplication::new(32, 16, 30);
+        headless.run(&application, 5).unwrap();
+
+        assert_eq!(1, application.initialized.get());
+        assert_eq!(5, application.inputs.get());
+        assert_eq!(5, application.renders.get());
+        assert_eq!(5, application.sounds.get());
+        assert_eq!(5, headless.frame_count());
+    }
+
+    #[test]
+    fn test_run_uses_fixed_size_buffers() {
+        let application = CountingApplication::default();
+        let mut headless = HeadlessApplication::new(32, 16, 30);
+        headless.run(&application, 1).unwrap();
+
+        assert_eq!(32 * 16, application.last_buffer_length.get());
+        assert_eq!(48_000 / 30, application.last_sample_count.get());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod field_diff;
+pub mod headless_application;
+pub mod replay_verifier;
@@ -0,0 +1,115 @@
+use handmade_hero_interface::application_error::{ApplicationError, Result};
+use handmade_hero_interface::game_state::GameState;
+use handmade_hero_interface::initialize_context::InitializeContext;
+use handmade_hero_platform::application_loader::ApplicationLoader;
+use handmade_hero_platform::playback_recorder::PlaybackRecorder;
+use headless_hero_rs::headless_application::HeadlessApplication;
+use headless_hero_rs::replay_verifier::verify_replay;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+struct Options {
+    plugin_directory: PathBuf,
+    width: u16,
+    height: u16,
+    frames_per_second: u16,
+    frame_count: u64,
+    replay: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let options = parse_options(std::env::args().skip(1))?;
+    let mut loader = ApplicationLoader::new(&options.plugin_directory);
+    if let Some(recording) = &options.replay {
+        return replay(&mut loader, recording);
+    }
+    let mut headless =
+        HeadlessApplication::new(options.width, options.height, options.frames_per_second);
+    for _ in 0..options.frame_count {
+        // Loading every frame picks up plugin rebuilds, just like the Windows host.
+        let context = InitializeContext {
+            state: headless.state_mut(),
+        };
+        let application = loader.load(context)?;
+        headless.run_frame(application);
+    }
+    println!("Simulated {} frames", headless.frame_count());
+    Ok(())
+}
+
+/// Replays a recording's input and fails if the game no longer reproduces the state
+/// stored in the recording's keyframes.
+fn replay(loader: &mut ApplicationLoader, recording: &Path) -> Result<()> {
+    let mut recorder = PlaybackRecorder::default();
+    recorder.start_playback_file(recording)?;
+    let mut state = GameState::new();
+    let context = InitializeContext { state: &mut state };
+    let application = loader.load(context)?;
+    let report = verify_replay(&mut recorder, application)?;
+    if let Some(divergence) = report.divergence() {
+        return Err(ApplicationError::new(divergence.to_string()));
+    }
+    println!(
+        "Replayed {} frames and matched {} keyframes ({})",
+        report.frame_count(),
+        report.keyframes_checked(),
+        report.mode()
+    );
+    Ok(())
+}
+
+fn parse_options(mut arguments: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        plugin_directory: exe_directory()?,
+        width: 960,
+        height: 540,
+        frames_per_second: 30,
+        frame_count: 300,
+        replay: None,
+    };
+    while let Some(argument) = arguments.next() {
+        let mut value = || {
+            arguments
+                .next()
+                .ok_or_else(|| ApplicationError::new(format!("Missing a value for {argument}")))
+        };
+        match argument.as_str() {
+            "--plugin-directory" => options.plugin_directory = PathBuf::from(value()?),
+            "--width" => options.width = parse_number(&argument, &value()?)?,
+            "--height" => options.height = parse_number(&argument, &value()?)?,
+            "--fps" => options.frames_per_second = parse_number(&argument, &value()?)?,
+            "--frames" => options.frame_count = parse_number(&argument, &value()?)?,
+            "--replay" => options.replay = Some(PathBuf::from(value()?)),
+            _ => {
+                return Err(ApplicationError::new(format!(
+                    "Unrecognized argument {argument}"
+                )));
+            }
+        }
+    }
+    if options.width == 0 || options.height == 0 || options.frames_per_second == 0 {
+        return Err(ApplicationError::new(
+            "The width, height and frame rate must be greater than zero",
+        ));
+    }
+    Ok(options)
+}
+
+fn parse_number<T>(argument: &str, value: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + 'static,
+{
+    value
+        .parse()
+        .map_err(|e| ApplicationError::wrap(format!("Invalid value for {argument}: {value}"), e))
+}
+
+fn exe_directory() -> Result<PathBuf> {
+    let current_exe_path = std::env::current_exe()
+        .map_err(|e| ApplicationError::wrap("Failed to retrieve the current executable path", e))?;
+    let current_directory = current_exe_path.parent().ok_or_else(|| {
+        ApplicationError::new("Failed to retrieve the current executable parent directory")
+    })?;
+    Ok(current_directory.to_path_buf())
+}
@@ -0,0 +1,264 @@
+use crate::field_diff::{FieldDifference, first_difference};
+use crate::headless_application::HeadlessApplication;
+use handmade_hero_interface::application::Application;
+use handmade_hero_interface::application_error::{ApplicationError, ErrorKind, Result};
+use handmade_hero_platform::playback_recorder::PlaybackRecorder;
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroU64;
+
+/// Hosts write a different amount of audio each frame depending on timing, so the sound
+/// state is not expected to match.
+const IGNORED_FIELDS: &[&str] = &["sound"];
+
+/// The first recorded keyframe whose state the re-simulated game did not reproduce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    frame: u64,
+    difference: FieldDifference,
+}
+
+impl Divergence {
+    #[inline]
+    #[must_use]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn difference(&self) -> &FieldDifference {
+        &self.difference
+    }
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Frame {} diverged at {}", self.frame, self.difference)
+    }
+}
+
+/// Which frames of a replay had a recorded state to compare against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Every frame is a keyframe, so a divergence is caught on the frame it happens.
+    EveryFrame,
+    /// Only every so many frames are compared, so a divergence is caught up to that
+    /// many frames late.
+    Keyframes(NonZeroU64),
+    /// Only the first frame is a keyframe, so nothing after it can be compared.
+    FirstFrameOnly,
+}
+
+impl ReplayMode {
+    fn from_keyframe_interval(interval: Option<NonZeroU64>) -> Self {
+        match interval {
+            Some(interval) if interval.get() == 1 => Self::EveryFrame,
+            Some(interval) => Self::Keyframes(interval),
+            None => Self::FirstFrameOnly,
+        }
+    }
+}
+
+impl Display for ReplayMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EveryFrame => write!(f, "compared every frame"),
+            Self::Keyframes(interval) => write!(f, "compared every {interval} frames"),
+            Self::FirstFrameOnly => write!(f, "only the first frame has a state to compare"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport {
+    mode: ReplayMode,
+    frame_count: u64,
+    keyframes_checked: u64,
+    divergence: Option<Divergence>,
+}
+
+impl ReplayReport {
+    /// Which frames were compared, depending on the recording's keyframe interval.
+    #[inline]
+    #[must_use]
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    /// The number of frames simulated before the replay finished or diverged.
+    #[inline]
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The number of keyframes, not counting the first, that were compared.
+    #[inline]
+    #[must_use]
+    pub fn keyframes_checked(&self) -> u64 {
+        self.keyframes_checked
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn divergence(&self) -> Option<&Divergence> {
+        self.divergence.as_ref()
+    }
+}
+
+/// Re-simulates a recording from its first keyframe using only its recorded input, and
+/// compares the game state against every later keyframe. Recordings made with a
+/// keyframe interval of 1 are compared frame by frame; the report's mode says which
+/// was done.
+///
+/// # Errors
+/// An error is returned if the recorder is not playing or the recording cannot be read.
+pub fn verify_replay(
+    recorder: &mut PlaybackRecorder,
+    application: &dyn Application,
+) -> Result<ReplayReport> {
+    let first = recorder
+        .playback()?
+        .ok_or_else(|| ApplicationError::new("There is no recording to replay"))?;
+    let state = first.state.ok_or_else(|| {
+        ApplicationError::new("The recording does not start with a keyframe")
+            .with_kind(ErrorKind::CorruptRecording)
+    })?;
+    let mut headless = HeadlessApplication::from_state(state);
+    *headless.input_mut() = first.input;
+    headless.run_frame(application);
+
+    let mut report = ReplayReport {
+        mode: ReplayMode::from_keyframe_interval(recorder.recorded_keyframe_interval()),
+        frame_count: 1,
+        keyframes_checked: 0,
+        divergence: None,
+    };
+    while let Some(frame) = recorder.playback()? {
+        if let Some(expected) = frame.state {
+            report.keyframes_checked += 1;
+            let difference = first_difference(&expected, headless.state(), IGNORED_FIELDS)?;
+            if let Some(difference) = difference {
+                report.divergence = Some(Divergence {
+                    frame: report.frame_count,
+                    difference,
+                });
+                break;
+            }
+        }
+        *headless.input_mut() = frame.input;
+        headless.run_frame(application);
+        report.frame_count += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::headless_application::HeadlessApplication;
+    use crate::replay_verifier::{ReplayMode, verify_replay};
+    use handmade_hero_interface::application::Application;
+    use handmade_hero_interface::application_error::Result;
+    use handmade_hero_interface::audio_context::AudioContext;
+    use handmade_hero_interface::initialize_context::InitializeContext;
+    use handmade_hero_interface::input_context::InputContext;
+    use handmade_hero_interface::render_context::RenderContext;
+    use handmade_hero_platform::playback_recorder::PlaybackRecorder;
+    use std::cell::Cell;
+    use std::num::NonZeroU64;
+    use std::path::PathBuf;
+
+    /// Moves the player by the mouse position, plus a drift that grows every frame to
+    /// simulate a game that depends on something besides its state and input.
+    #[derive(Debug, Default)]
+    struct MovingApplication {
+        drift: Option<Cell<f32>>,
+    }
+
+    impl Application for MovingApplication {
+        fn initialize(&self, _context: InitializeContext<'_>) -> Result<()> {
+            Ok(())
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        fn process_input(&self, context: InputContext<'_>) {
+            let mut delta = context.input.mouse().x() as f32 * 0.01;
+            if let Some(drift) = &self.drift {
+                drift.set(drift.get() + 0.001);
+                delta += drift.get();
+            }
+            let player = context.state.player_mut();
+            let coordinate = player.coordinate().shifted(delta, 0f32);
+            player.set_coordinates(coordinate);
+        }
+
+        fn render(&self, _context: RenderContext<'_>) {}
+
+        fn write_sound(&self, _context: AudioContext<'_>) {}
+    }
+
+    fn record(name: &str, keyframe_interval: u64) -> (PlaybackRecorder, PathBuf) {
+        let directory =
+            std::env::temp_dir().join(format!("handmade_hero_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut recorder = PlaybackRecorder::new(&directory);
+        recorder.set_keyframe_interval(NonZeroU64::new(keyframe_interval));
+        recorder.start_recording(0, 30f32).unwrap();
+        let application = MovingApplication::default();
+        let mut headless = HeadlessApplication::new(64, 32, 30);
+        for index in 0..6 {
+            headless.input_mut().mouse_mut().set_x(index);
+            recorder.record(headless.input(), headless.state()).unwrap();
+            headless.run_frame(&application);
+        }
+        recorder.start_playback(0).unwrap();
+        (recorder, directory)
+    }
+
+    #[test]
+    fn test_deterministic_replay() {
+        let (mut recorder, directory) = record("deterministic_replay", 2);
+        let report = verify_replay(&mut recorder, &MovingApplication::default()).unwrap();
+        assert_eq!(
+            ReplayMode::Keyframes(NonZeroU64::new(2).unwrap()),
+            report.mode()
+        );
+        assert_eq!(None, report.divergence());
+        assert_eq!(6, report.frame_count());
+        assert_eq!(2, report.keyframes_checked());
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_reports_first_divergence() {
+        let (mut recorder, directory) = record("divergent_replay", 2);
+        let application = MovingApplication {
+            drift: Some(Cell::new(0f32)),
+        };
+        let report = verify_replay(&mut recorder, &application).unwrap();
+        let divergence = report.divergence().unwrap();
+        assert_eq!(2, divergence.frame());
+        assert!(
+            divergence
+                .difference()
+                .path
+                .starts_with("player.coordinate")
+        );
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_every_frame_catches_divergence_immediately() {
+        let (mut recorder, directory) = record("every_frame_replay", 1);
+        let application = MovingApplication {
+            drift: Some(Cell::new(0f32)),
+        };
+        let report = verify_replay(&mut recorder, &application).unwrap();
+        assert_eq!(ReplayMode::EveryFrame, report.mode());
+        assert_eq!(1, report.divergence().unwrap().frame());
+        drop(recorder);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,8 +1,6 @@
-use crate::application_loader::ApplicationLoader;
 use crate::direct_sound::DirectSound;
 use crate::direct_sound_buffer::DirectSoundBuffer;
 use crate::performance_counter::PerformanceCounter;
-use crate::playback_recorder::PlaybackRecorder;
 use core::slice;
 use handmade_hero_interface::application::Application;
 use handmade_hero_interface::application_error::{ApplicationError, Result};
@@ -13,11 +11,17 @@ use handmade_hero_interface::game_state::GameState;
 use handmade_hero_interface::initialize_context::InitializeContext;
 use handmade_hero_interface::input_context::InputContext;
 use handmade_hero_interface::input_state::InputState;
+use handmade_hero_interface::platform_status::PlatformStatus;
 use handmade_hero_interface::render_context::RenderContext;
+use handmade_hero_interface::render_target::{RenderTarget, RowOrder};
 use handmade_hero_interface::stereo_sample::StereoSample;
 use handmade_hero_interface::units::si::length::pixel;
+use handmade_hero_platform::application_loader::ApplicationLoader;
+use handmade_hero_platform::playback_controller::PlaybackController;
+use handmade_hero_platform::playback_recorder::PlaybackRecorder;
 use std::cmp::Ordering;
 use std::ffi::c_void;
+use std::num::NonZeroU64;
 use std::path::PathBuf;
 use std::time::Duration;
 use uom::si::f32::Time;
@@ -34,8 +38,9 @@ use windows::Win32::Graphics::Gdi::{
 use windows::Win32::Media::{TIMERR_NOERROR, timeBeginPeriod};
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    GetKeyState, VIRTUAL_KEY, VK_A, VK_CONTROL, VK_D, VK_DOWN, VK_E, VK_ESCAPE, VK_F4, VK_L,
-    VK_LBUTTON, VK_LEFT, VK_MBUTTON, VK_Q, VK_RBUTTON, VK_RIGHT, VK_S, VK_UP, VK_W,
+    GetKeyState, VIRTUAL_KEY, VK_1, VK_2, VK_3, VK_4, VK_A, VK_CONTROL, VK_D, VK_DOWN, VK_E,
+    VK_ESCAPE, VK_F4, VK_HOME, VK_L, VK_LBUTTON, VK_LEFT, VK_MBUTTON, VK_OEM_COMMA, VK_OEM_PERIOD,
+    VK_P, VK_Q, VK_RBUTTON, VK_RIGHT, VK_S, VK_SPACE, VK_UP, VK_W,
 };
 use windows::Win32::UI::Input::XboxController::{
     XINPUT_GAMEPAD, XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B, XINPUT_GAMEPAD_BACK,
@@ -76,6 +81,9 @@ pub struct Win32Application {
     sound_safety_bytes: u32,
     closing: bool,
     recording_state: RecordingState,
+    recording_slot: usize,
+    playback_controller: PlaybackController,
+    frame_time: Duration,
 }
 
 impl Win32Application {
@@ -91,6 +99,9 @@ impl Win32Application {
             sound_safety_bytes: 0,
             closing: false,
             recording_state: RecordingState::None,
+            recording_slot: 0,
+            playback_controller: PlaybackController::new(),
+            frame_time: Duration::ZERO,
         }
     }
 
@@ -259,6 +270,37 @@ impl Win32Application {
                 mapped_button.reset_half_transition_count();
             }
         }
+        if is_down {
+            // Hitting '1' through '4' selects the recording slot used by 'L' and 'P'.
+            let slot = match virtual_key {
+                VK_1 => Some(0),
+                VK_2 => Some(1),
+                VK_3 => Some(2),
+                VK_4 => Some(3),
+                _ => None,
+            };
+            if let Some(slot) = slot
+                && !matches!(self.recording_state, RecordingState::Recording)
+            {
+                self.recording_slot = slot;
+            }
+        }
+        if is_down && matches!(self.recording_state, RecordingState::Playing) {
+            // While playing, SPACE pauses, ',' and '.' step a frame back and forward,
+            // and HOME jumps to the start.
+            match virtual_key {
+                VK_SPACE => self.playback_controller.toggle_pause(),
+                VK_OEM_COMMA => self.playback_controller.step_backward(),
+                VK_OEM_PERIOD => self.playback_controller.step_forward(),
+                VK_HOME => self.playback_controller.seek(0),
+                _ => {}
+            }
+        }
+        if virtual_key == VK_P && is_down {
+            // Hitting 'P' plays back whatever is saved in the selected slot, even if it
+            // was recorded in an earlier session.
+            self.recording_state = RecordingState::Playing;
+        }
         if virtual_key == VK_L && is_down {
             // Hitting 'L' begins a recording sessions.
             // Hitting 'L' again causes the recording session to end.
@@ -413,6 +455,10 @@ impl Win32Application {
         let exe_directory = Self::exe_directory()?;
         let mut loader = ApplicationLoader::new(&exe_directory);
         let mut recorder = PlaybackRecorder::new(&exe_directory);
+        // Store a full snapshot every 10 seconds so long recordings can be seeked.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let keyframe_interval = NonZeroU64::new((game_update_hertz * 10f32) as u64);
+        recorder.set_keyframe_interval(keyframe_interval);
         let mut counter = PerformanceCounter::start();
         loop {
             let mut message = MSG::default();
@@ -432,16 +478,31 @@ impl Win32Application {
             };
             let application = loader.load(initialize_context)?;
 
+            self.synchronize_recorder(&mut recorder, game_update_hertz);
+
             // It seems our audio can't really use playback. The computation of how many bytes
             // to write depends on how fast the previous frame took to generate. Since this will
             // be different each frame, trying to restore the sound theta causes skipping and
             // other sound artifacts. So we just capture theta upfront and restore it after.
             // Hopefully this gets addressed in a later episode.
             if let RecordingState::Playing = self.recording_state {
-                if let Some(state) = recorder.playback().unwrap_or_default() {
-                    (self.input, self.state) = (state.input, state.state);
-                } else {
-                    recorder.reset_playback().unwrap_or_default(); // We miss a frame here
+                // Between keyframes, the game re-simulates the state from the input. Seeking
+                // can require simulating several frames at once. While paused, there is
+                // nothing to simulate and the current frame is just rendered again.
+                let frames = self
+                    .playback_controller
+                    .update(&mut recorder)
+                    .unwrap_or_default();
+                for frame in frames {
+                    self.input = frame.input;
+                    if let Some(state) = frame.state {
+                        self.state = state;
+                    }
+                    let context = InputContext {
+                        input: &self.input,
+                        state: &mut self.state,
+                    };
+                    application.process_input(context);
                 }
             } else {
                 self.poll_controller_state();
@@ -455,21 +516,34 @@ impl Win32Application {
                         .record(&self.input, &self.state)
                         .unwrap_or_default(); // Ignore errors
                 }
-            }
 
-            let context = InputContext {
-                input: &self.input,
-                state: &mut self.state,
-            };
-            application.process_input(context);
-
-            if let Some(ref mut bitmap_buffer) = self.bitmap_buffer {
-                let context = RenderContext {
+                let context = InputContext {
                     input: &self.input,
                     state: &mut self.state,
-                    buffer: bitmap_buffer,
                 };
-                application.render(context);
+                application.process_input(context);
+            }
+
+            if let Some(ref mut bitmap_buffer) = self.bitmap_buffer {
+                // The bitmap's height is negative, so its rows are stored top-down.
+                let header = &self.bitmap_info.bmiHeader;
+                let width = usize::try_from(header.biWidth).unwrap_or_default();
+                let height = usize::try_from(header.biHeight.unsigned_abs()).unwrap_or_default();
+                let target =
+                    RenderTarget::new(bitmap_buffer, width, height, width, RowOrder::TopDown);
+                let status = PlatformStatus {
+                    frame_time: Time::new::<second>(self.frame_time.as_secs_f32()),
+                    recording: self.playback_controller.status(&recorder),
+                };
+                if let Ok(target) = target {
+                    let context = RenderContext {
+                        input: &self.input,
+                        state: &mut self.state,
+                        target,
+                        status,
+                    };
+                    application.render(context);
+                }
             }
 
             if let Some(sound_index) = self.sound_index
@@ -500,6 +574,31 @@ impl Win32Application {
         }
     }
 
+    fn synchronize_recorder(&mut self, recorder: &mut PlaybackRecorder, game_update_hertz: f32) {
+        let result = match self.recording_state {
+            RecordingState::None => recorder.stop(),
+            RecordingState::Recording => {
+                if recorder.recording_slot() == Some(self.recording_slot) {
+                    Ok(())
+                } else {
+                    recorder.start_recording(self.recording_slot, game_update_hertz)
+                }
+            }
+            RecordingState::Playing => {
+                if recorder.playing_slot() == Some(self.recording_slot) {
+                    Ok(())
+                } else {
+                    self.playback_controller = PlaybackController::new();
+                    recorder.start_playback(self.recording_slot)
+                }
+            }
+        };
+        if result.is_err() {
+            // E.g., trying to play back an empty slot.
+            self.recording_state = RecordingState::None;
+        }
+    }
+
     fn calculate_sound_safety_bytes(&mut self, game_update_hertz: f32) -> u32 {
         let sound_state = self.state.sound();
         let sound_samples_per_second = sound_state.samples_per_second();
@@ -809,9 +908,10 @@ impl Win32Application {
         }
     }
 
-    fn wait_for_framerate(&self, counter: &mut PerformanceCounter, is_sleep_granular: bool) {
+    fn wait_for_framerate(&mut self, counter: &mut PerformanceCounter, is_sleep_granular: bool) {
         let mut metrics = counter.metrics();
         let mut time_elapsed = metrics.elapsed_time();
+        self.frame_time = time_elapsed;
         let frame_duration = Duration::from_secs_f32(self.state.frame_duration().get::<second>());
         while time_elapsed < frame_duration {
             if is_sleep_granular {